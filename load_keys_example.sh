@@ -1,3 +1,6 @@
 #!/bin/bash
-export GEMINI_API_KEY=""
-export GROQ_API_KEY=""
+# Only needed for the built-in backends without a config file; prefer
+# api_key_file, api_key_command or api_key_keyring in config.toml.
+# Never paste the keys themselves here.
+export GEMINI_API_KEY="$(pass show gemini)"
+export GROQ_API_KEY="$(pass show groq)"
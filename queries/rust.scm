@@ -0,0 +1,27 @@
+; Rust
+
+; Functions anywhere in the tree, including nested ones and those in modules
+(function_item body: (block) @body) @function
+
+; Methods and trait default methods carry the impl or trait header
+[
+  (impl_item body: (declaration_list (function_item) @function))
+  (trait_item body: (declaration_list (function_item) @function))
+] @context
+
+; todo!(), unimplemented!() and panic!("not implemented")
+((macro_invocation
+   macro: [
+     (identifier) @_name
+     (scoped_identifier name: (identifier) @_name)
+   ]) @placeholder
+ (#any-of? @_name "todo" "unimplemented"))
+
+((macro_invocation
+   macro: [
+     (identifier) @_name
+     (scoped_identifier name: (identifier) @_name)
+   ]
+   (token_tree (string_literal) @_message)) @placeholder
+ (#eq? @_name "panic")
+ (#match? @_message "(?i)not implemented"))
@@ -0,0 +1,88 @@
+; TypeScript and TSX
+
+; Functions and methods anywhere in the tree
+[
+  (function_declaration body: (statement_block) @body)
+  (generator_function_declaration body: (statement_block) @body)
+  (function_expression body: (statement_block) @body)
+  (generator_function body: (statement_block) @body)
+  (arrow_function body: (statement_block) @body)
+  (method_definition body: (statement_block) @body)
+] @function
+
+; Exported declarations keep `export` in the signature
+(export_statement
+  declaration: [
+    (function_declaration body: (statement_block) @body)
+    (generator_function_declaration body: (statement_block) @body)
+  ]) @function
+
+; Function values take their signature and docs from what they are
+; assigned to, so we get 'export const ...' and not just '() =>'
+(variable_declarator
+  value: [
+    (function_expression body: (statement_block) @body)
+    (generator_function body: (statement_block) @body)
+    (arrow_function body: (statement_block) @body)
+  ]) @function
+
+(pair
+  value: [
+    (function_expression body: (statement_block) @body)
+    (generator_function body: (statement_block) @body)
+    (arrow_function body: (statement_block) @body)
+  ]) @function
+
+(public_field_definition
+  value: [
+    (function_expression body: (statement_block) @body)
+    (generator_function body: (statement_block) @body)
+    (arrow_function body: (statement_block) @body)
+  ]) @function
+
+(lexical_declaration
+  (variable_declarator
+    value: [
+      (function_expression body: (statement_block) @body)
+      (generator_function body: (statement_block) @body)
+      (arrow_function body: (statement_block) @body)
+    ])) @function
+
+(variable_declaration
+  (variable_declarator
+    value: [
+      (function_expression body: (statement_block) @body)
+      (generator_function body: (statement_block) @body)
+      (arrow_function body: (statement_block) @body)
+    ])) @function
+
+(export_statement
+  declaration: (lexical_declaration
+    (variable_declarator
+      value: [
+        (function_expression body: (statement_block) @body)
+        (generator_function body: (statement_block) @body)
+        (arrow_function body: (statement_block) @body)
+      ]))) @function
+
+; Class members carry the class header and fields
+[
+  (class_declaration body: (class_body [(method_definition) (public_field_definition)] @function))
+  (class body: (class_body [(method_definition) (public_field_definition)] @function))
+  (abstract_class_declaration body: (class_body [(method_definition) (public_field_definition)] @function))
+] @context
+
+(export_statement
+  declaration: [
+    (class_declaration body: (class_body [(method_definition) (public_field_definition)] @function))
+    (class body: (class_body [(method_definition) (public_field_definition)] @function))
+    (abstract_class_declaration body: (class_body [(method_definition) (public_field_definition)] @function))
+  ]) @context
+
+; throw new Error("not implemented")
+((throw_statement
+   (new_expression
+     constructor: (identifier) @_class
+     arguments: (arguments (string) @_message))) @placeholder
+ (#eq? @_class "Error")
+ (#match? @_message "(?i)(not implemented|todo)"))
@@ -0,0 +1,315 @@
+use crate::ai::backend::{
+    GenerationOptions, LLMBackend, LLMResponse, TokenUsage,
+};
+use crate::ai::helpers::{build_user_prompt, clean_output, system_prompt};
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_ANTHROPIC_URL: &str = "https://api.anthropic.com";
+
+/// Version of the Messages API the request and response structs follow
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Highest temperature the Messages API accepts
+pub const MAX_TEMPERATURE: f32 = 1.0;
+
+/// Anthropic Messages API backend
+pub struct AnthropicBackend {
+    pub base_url: String,
+    pub api_key: String,
+    pub model: String,
+    pub max_tokens: u32,
+    pub temperature: Option<f32>,
+    pub stop_sequences: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct MessagesRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    system: &'a str,
+    messages: Vec<Message<'a>>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop_sequences: &'a [String],
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct MessagesResponse {
+    content: Vec<ContentBlock>,
+    usage: Usage,
+}
+
+#[derive(Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    // Only text blocks carry text
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    input_tokens: u32,
+    output_tokens: u32,
+}
+
+#[derive(Deserialize)]
+struct ErrorResponse {
+    error: ErrorDetail,
+}
+
+#[derive(Deserialize)]
+struct ErrorDetail {
+    #[serde(rename = "type")]
+    kind: String,
+    message: String,
+}
+
+#[async_trait]
+impl LLMBackend for AnthropicBackend {
+    async fn generate_function(
+        &self,
+        signature: &str,
+        doc_comment: Option<&str>,
+        instructions: Option<&str>,
+        context_snippets: Option<&[String]>,
+        language: &str,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        // the configured one was checked with the config file
+        if let Some(temperature) = options.temperature
+            && !(0.0..=MAX_TEMPERATURE).contains(&temperature)
+        {
+            bail!(
+                "temperature: must be between 0 and {MAX_TEMPERATURE} for \
+                 Anthropic, got {temperature}"
+            );
+        }
+
+        let client = Client::new();
+
+        let system_prompt = system_prompt(language);
+
+        let user_prompt = build_user_prompt(
+            signature,
+            doc_comment,
+            instructions,
+            context_snippets,
+        );
+
+        let url = format!("{}/v1/messages", self.base_url.trim_end_matches('/'));
+
+        let request_body = MessagesRequest {
+            model: options.model(&self.model),
+            max_tokens: options.max_tokens.unwrap_or(self.max_tokens),
+            temperature: options.temperature.or(self.temperature),
+            system: &system_prompt,
+            messages: vec![Message { role: "user", content: &user_prompt }],
+            stop_sequences: match options.stop.is_empty() {
+                true => &self.stop_sequences,
+                false => &options.stop,
+            },
+        };
+
+        let resp = client
+            .post(&url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(&request_body)
+            .send()
+            .await?;
+
+        let status = resp.status();
+        let text = resp.text().await?;
+        if !status.is_success() {
+            // The error body explains far more than the status code
+            match serde_json::from_str::<ErrorResponse>(&text) {
+                Ok(e) => bail!(
+                    "Anthropic API error ({}): {}",
+                    e.error.kind,
+                    e.error.message
+                ),
+                Err(_) => bail!("Anthropic API error: HTTP {status}"),
+            }
+        }
+
+        let resp: MessagesResponse = serde_json::from_str(&text)?;
+        Ok(response_from(resp))
+    }
+}
+
+//---------------------- Backend specific helpers -----------------------------
+
+/// Joins the text blocks of a response and maps its usage
+fn response_from(resp: MessagesResponse) -> LLMResponse {
+    let output: String = resp
+        .content
+        .iter()
+        .filter(|block| block.kind == "text")
+        .map(|block| block.text.as_str())
+        .collect();
+
+    LLMResponse {
+        body: clean_output(&output),
+        usage: Some(TokenUsage {
+            prompt_tokens: resp.usage.input_tokens,
+            completion_tokens: resp.usage.output_tokens,
+            total_tokens: resp.usage.input_tokens + resp.usage.output_tokens,
+        }),
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock_server::MockServer;
+
+    const REQUEST: &str =
+        include_str!("../../tests/fixtures/anthropic/request.json");
+    const RESPONSE: &str =
+        include_str!("../../tests/fixtures/anthropic/response.json");
+    const ERROR: &str = include_str!("../../tests/fixtures/anthropic/error.json");
+
+    fn backend(base_url: &str) -> AnthropicBackend {
+        AnthropicBackend {
+            base_url: base_url.to_string(),
+            api_key: "sk-ant-test".to_string(),
+            model: "claude-sonnet-4-5".to_string(),
+            max_tokens: 1024,
+            temperature: None,
+            stop_sequences: vec!["\n}".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_request_matches_fixture() {
+        let stop_sequences = vec!["\n}".to_string()];
+        let system = system_prompt("rust");
+        let request = MessagesRequest {
+            model: "claude-sonnet-4-5",
+            max_tokens: 1024,
+            temperature: None,
+            system: &system,
+            messages: vec![Message {
+                role: "user",
+                content: "/// Adds two numbers\nfn add(a: u32, b: u32) -> u32 {",
+            }],
+            stop_sequences: &stop_sequences,
+        };
+
+        let expected: serde_json::Value = serde_json::from_str(REQUEST).unwrap();
+        assert_eq!(serde_json::to_value(&request).unwrap(), expected);
+
+        // Stop sequences are optional
+        let request = MessagesRequest { stop_sequences: &[], ..request };
+        let value = serde_json::to_value(&request).unwrap();
+        assert!(value.get("stop_sequences").is_none());
+    }
+
+    #[test]
+    fn test_response_fixture_maps_usage() {
+        let resp: MessagesResponse = serde_json::from_str(RESPONSE).unwrap();
+        let resp = response_from(resp);
+
+        assert_eq!(resp.body, "a + b");
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 48);
+        assert_eq!(usage.completion_tokens, 7);
+        assert_eq!(usage.total_tokens, 55);
+    }
+
+    #[test]
+    fn test_non_text_blocks_are_skipped() {
+        let resp: MessagesResponse = serde_json::from_str(
+            r#"{"content": [
+                {"type": "thinking", "thinking": "sum them", "signature": "x"},
+                {"type": "text", "text": "a + b"}
+            ], "usage": {"input_tokens": 1, "output_tokens": 2}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(response_from(resp).body, "a + b");
+    }
+
+    #[tokio::test]
+    async fn test_messages_request_headers() {
+        let server = MockServer::start(200, RESPONSE).await;
+
+        let resp = backend(&server.url)
+            .generate_function(
+                "fn add(a: u32, b: u32) -> u32",
+                Some("/// Adds two numbers"),
+                None,
+                None,
+                "rust",
+                &GenerationOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.body, "a + b");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/v1/messages");
+        assert_eq!(request.headers["x-api-key"], "sk-ant-test");
+        assert_eq!(request.headers["anthropic-version"], ANTHROPIC_VERSION);
+        assert_eq!(
+            request.body,
+            serde_json::from_str::<serde_json::Value>(REQUEST).unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_out_of_range_temperature_is_rejected() {
+        let server = MockServer::start(200, RESPONSE).await;
+
+        let options = GenerationOptions {
+            temperature: Some(1.5),
+            ..Default::default()
+        };
+        let err = backend(&server.url)
+            .generate_function("fn f()", None, None, None, "rust", &options)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "temperature: must be between 0 and 1 for Anthropic, got 1.5"
+        );
+        assert!(server.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_error_body_is_reported() {
+        let server = MockServer::start(401, ERROR).await;
+
+        let err = backend(&server.url)
+            .generate_function(
+                "fn f()",
+                None,
+                None,
+                None,
+                "rust",
+                &GenerationOptions::default(),
+            )
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Anthropic API error (authentication_error): invalid x-api-key"
+        );
+    }
+}
@@ -1,6 +1,7 @@
-use anyhow::Result;
+use anyhow::{Result, bail};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct TokenUsage {
@@ -15,6 +16,34 @@ pub struct LLMResponse {
     pub usage: Option<TokenUsage>,
 }
 
+/// Receives the text of a generation as it arrives
+pub type DeltaSink = Arc<dyn Fn(&str) + Send + Sync>;
+
+/// Per-request overrides of the settings a backend was configured with
+#[derive(Deserialize, Clone, Default)]
+pub struct GenerationOptions {
+    pub model: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    /// Sequences ending the generation, replacing any configured ones
+    #[serde(default)]
+    pub stop: Vec<String>,
+    /// Streams the raw output to this sink, if the backend can. The body
+    /// returned is still the complete, cleaned one.
+    #[serde(skip)]
+    pub on_delta: Option<DeltaSink>,
+    /// Whether the body is closed by a `}`, as told by the language backend
+    #[serde(skip)]
+    pub braced_body: bool,
+}
+
+impl GenerationOptions {
+    /// The requested model, or the configured `model`
+    pub fn model<'a>(&'a self, model: &'a str) -> &'a str {
+        self.model.as_deref().unwrap_or(model)
+    }
+}
+
 /// Generic LLM Backend trait
 #[async_trait]
 pub trait LLMBackend: Send + Sync {
@@ -22,7 +51,33 @@ pub trait LLMBackend: Send + Sync {
         &self,
         signature: &str,
         doc_comment: Option<&str>,
+        instructions: Option<&str>,
         context_snippets: Option<&[String]>,
         language: &str,
+        options: &GenerationOptions,
     ) -> Result<LLMResponse>;
+
+    /// Whether the backend sends its output to
+    /// [`GenerationOptions::on_delta`] as it arrives. The others only
+    /// return the complete body.
+    fn supports_streaming(&self) -> bool {
+        false
+    }
+
+    /// Whether the backend is set up for fill-in-the-middle prompts, which
+    /// are then preferred over [`LLMBackend::generate_function`]
+    fn supports_fim(&self) -> bool {
+        false
+    }
+
+    /// Generates the code between `prefix` and `suffix` verbatim, using the
+    /// sentinel tokens of the model
+    async fn fill_in_middle(
+        &self,
+        _prefix: &str,
+        _suffix: &str,
+        _options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        bail!("backend does not support fill-in-the-middle")
+    }
 }
@@ -0,0 +1,435 @@
+use crate::ai::anthropic::{
+    AnthropicBackend, DEFAULT_ANTHROPIC_URL, MAX_TEMPERATURE,
+};
+use crate::ai::backend::LLMBackend;
+use crate::ai::fim::FimConfig;
+use crate::ai::gemini::{DEFAULT_GEMINI_URL, GeminiBackend};
+use crate::ai::groq_gpt_oss::GGPTOSSBackend;
+use crate::ai::keys::KeySource;
+use crate::ai::llama_cpp::{DEFAULT_LLAMA_CPP_URL, LlamaCppBackend};
+use crate::ai::ollama::{DEFAULT_OLLAMA_URL, OllamaBackend};
+use crate::ai::openai_compat::OpenAICompatBackend;
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// API a configured backend talks to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Provider {
+    Gemini,
+    Groq,
+    OpenaiCompat,
+    Anthropic,
+    Ollama,
+    LlamaCpp,
+}
+
+/// A `[backends.<name>]` table of the config file. Options only some
+/// providers understand are rejected for the others by [`validate`].
+///
+/// [`validate`]: BackendConfig::validate
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackendConfig {
+    pub provider: Provider,
+    pub model: Option<String>,
+    pub base_url: Option<String>,
+    /// Environment variable holding the API key
+    pub api_key_env: Option<String>,
+    /// File holding the API key
+    pub api_key_file: Option<PathBuf>,
+    /// Shell command printing the API key
+    pub api_key_command: Option<String>,
+    /// Keyring entry holding the API key
+    pub api_key_keyring: Option<String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+    /// Header carrying the raw key instead of `Authorization: Bearer`
+    pub auth_header: Option<String>,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
+    pub fim: Option<FimConfig>,
+    /// Inline GBNF grammar; an empty string disables the default one
+    pub grammar: Option<String>,
+    pub grammar_file: Option<PathBuf>,
+}
+
+impl fmt::Display for Provider {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Provider::Gemini => "gemini",
+            Provider::Groq => "groq",
+            Provider::OpenaiCompat => "openai_compat",
+            Provider::Anthropic => "anthropic",
+            Provider::Ollama => "ollama",
+            Provider::LlamaCpp => "llama_cpp",
+        })
+    }
+}
+
+impl Provider {
+    /// Model used when the table names none
+    fn default_model(self) -> Option<&'static str> {
+        match self {
+            Provider::Gemini => Some("Gemini-2.5-flash"),
+            Provider::Groq => Some("openai/gpt-oss-20b"),
+            _ => None,
+        }
+    }
+
+    fn default_key_env(self) -> Option<&'static str> {
+        match self {
+            Provider::Gemini => Some("GEMINI_API_KEY"),
+            Provider::Groq => Some("GROQ_API_KEY"),
+            Provider::Anthropic => Some("ANTHROPIC_API_KEY"),
+            _ => None,
+        }
+    }
+
+    fn max_temperature(self) -> f32 {
+        match self {
+            Provider::Anthropic => MAX_TEMPERATURE,
+            _ => 2.0,
+        }
+    }
+}
+
+impl BackendConfig {
+    /// Checks the table of backend `name`, naming the offending key
+    pub fn validate(&self, name: &str) -> Result<()> {
+        let key = |field: &str| format!("backends.{name}.{field}");
+        let provider = self.provider;
+
+        let set = [
+            ("model", self.model.is_some()),
+            ("base_url", self.base_url.is_some()),
+            ("api_key_env", self.api_key_env.is_some()),
+            ("api_key_file", self.api_key_file.is_some()),
+            ("api_key_command", self.api_key_command.is_some()),
+            ("api_key_keyring", self.api_key_keyring.is_some()),
+            ("auth_header", self.auth_header.is_some()),
+            ("headers", !self.headers.is_empty()),
+            ("stop_sequences", !self.stop_sequences.is_empty()),
+            ("fim", self.fim.is_some()),
+            ("grammar", self.grammar.is_some()),
+            ("grammar_file", self.grammar_file.is_some()),
+        ];
+        let supported: &[&str] = match provider {
+            Provider::Gemini => &["model", "base_url", "api_key"],
+            Provider::Groq => &["model", "api_key"],
+            Provider::OpenaiCompat => {
+                &["model", "base_url", "api_key", "auth_header", "headers"]
+            }
+            Provider::Anthropic => {
+                &["model", "base_url", "api_key", "stop_sequences"]
+            }
+            Provider::Ollama => &["model", "base_url", "fim"],
+            Provider::LlamaCpp => {
+                &["base_url", "fim", "grammar", "grammar_file"]
+            }
+        };
+        for (field, is_set) in set {
+            // any key source goes where a key does
+            let option = match field.starts_with("api_key_") {
+                true => "api_key",
+                false => field,
+            };
+            if is_set && !supported.contains(&option) {
+                bail!("{}: not supported by provider {provider}", key(field));
+            }
+        }
+
+        let mut key_sources = set
+            .iter()
+            .filter(|(field, is_set)| *is_set && field.starts_with("api_key_"));
+        if let (Some((first, _)), Some((second, _))) =
+            (key_sources.next(), key_sources.next())
+        {
+            bail!("{}: cannot be combined with {}", key(second), key(first));
+        }
+
+        let needs_model = supported.contains(&"model");
+        if needs_model
+            && self.model.is_none()
+            && provider.default_model().is_none()
+        {
+            bail!("{}: required by provider {provider}", key("model"));
+        }
+        if provider == Provider::OpenaiCompat && self.base_url.is_none() {
+            bail!("{}: required by provider {provider}", key("base_url"));
+        }
+        if let Some(url) = &self.base_url
+            && reqwest::Url::parse(url).is_err()
+        {
+            bail!("{}: invalid URL {url:?}", key("base_url"));
+        }
+
+        if let Some(temperature) = self.temperature {
+            let max = provider.max_temperature();
+            if !(0.0..=max).contains(&temperature) {
+                bail!(
+                    "{}: must be between 0 and {max}, got {temperature}",
+                    key("temperature")
+                );
+            }
+        }
+        if self.max_tokens == Some(0) {
+            bail!("{}: must be greater than 0", key("max_tokens"));
+        }
+
+        if let Some(fim) = &self.fim
+            && let Err(e) = fim.template()
+        {
+            bail!("{}: {e}", key("fim"));
+        }
+        if self.grammar.is_some() && self.grammar_file.is_some() {
+            bail!(
+                "{}: cannot be combined with {}",
+                key("grammar_file"),
+                key("grammar")
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Instantiates the backend. Fails when its API key or grammar file
+    /// cannot be read.
+    pub fn build(&self) -> Result<Arc<dyn LLMBackend>> {
+        let model = self
+            .model
+            .clone()
+            .or(self.provider.default_model().map(str::to_string))
+            .unwrap_or_default();
+        let base_url = |default: &str| {
+            self.base_url.clone().unwrap_or_else(|| default.to_string())
+        };
+
+        let backend: Arc<dyn LLMBackend> = match self.provider {
+            Provider::Gemini => Arc::new(GeminiBackend {
+                base_url: base_url(DEFAULT_GEMINI_URL),
+                api_key: self.required_key()?,
+                model,
+                temperature: self.temperature,
+                max_tokens: self.max_tokens,
+            }),
+            Provider::Groq => Arc::new(GGPTOSSBackend {
+                api_key: self.required_key()?,
+                model,
+                temperature: self.temperature,
+                max_tokens: self.max_tokens,
+            }),
+            Provider::OpenaiCompat => Arc::new(OpenAICompatBackend {
+                base_url: base_url(""),
+                model,
+                api_key: self.api_key()?,
+                auth_header: self.auth_header.clone(),
+                headers: self.headers.clone(),
+                temperature: self.temperature,
+                max_tokens: self.max_tokens,
+            }),
+            Provider::Anthropic => Arc::new(AnthropicBackend {
+                base_url: base_url(DEFAULT_ANTHROPIC_URL),
+                api_key: self.required_key()?,
+                model,
+                max_tokens: self.max_tokens.unwrap_or(1024),
+                temperature: self.temperature,
+                stop_sequences: self.stop_sequences.clone(),
+            }),
+            Provider::Ollama => Arc::new(OllamaBackend {
+                base_url: base_url(DEFAULT_OLLAMA_URL),
+                model,
+                fim: self.fim.as_ref().map(FimConfig::template).transpose()?,
+                temperature: self.temperature,
+                max_tokens: self.max_tokens,
+            }),
+            Provider::LlamaCpp => {
+                let grammar = match &self.grammar_file {
+                    Some(path) => Some(
+                        std::fs::read_to_string(path).with_context(|| {
+                            format!("reading {}", path.display())
+                        })?,
+                    ),
+                    None => self.grammar.clone(),
+                };
+                Arc::new(LlamaCppBackend {
+                    base_url: base_url(DEFAULT_LLAMA_CPP_URL),
+                    n_predict: self.max_tokens.unwrap_or(512),
+                    unconstrained: grammar.as_deref() == Some(""),
+                    grammar: grammar.filter(|g| !g.is_empty()),
+                    fim: self.fim.as_ref().map(FimConfig::template).transpose()?,
+                    temperature: self.temperature,
+                })
+            }
+        };
+
+        Ok(backend)
+    }
+
+    /// Where the API key comes from, the provider's variable by default
+    fn key_source(&self) -> Option<KeySource> {
+        if let Some(path) = &self.api_key_file {
+            return Some(KeySource::File(path.clone()));
+        }
+        if let Some(command) = &self.api_key_command {
+            return Some(KeySource::Command(command.clone()));
+        }
+        if let Some(user) = &self.api_key_keyring {
+            return Some(KeySource::Keyring(user.clone()));
+        }
+        self.api_key_env
+            .as_deref()
+            .or(self.provider.default_key_env())
+            .map(|var| KeySource::Env(var.to_string()))
+    }
+
+    /// Reads the API key, if the backend has one
+    fn api_key(&self) -> Result<Option<String>> {
+        self.key_source().map(|source| source.read()).transpose()
+    }
+
+    fn required_key(&self) -> Result<String> {
+        match self.api_key()? {
+            Some(key) => Ok(key),
+            None => bail!("no API key source configured"),
+        }
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(table: &str) -> BackendConfig {
+        toml::from_str(table).unwrap()
+    }
+
+    fn validation_error(table: &str) -> String {
+        parse(table).validate("b").unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_valid_backends() {
+        for table in [
+            "provider = \"gemini\"\ntemperature = 0.2",
+            "provider = \"groq\"\nmodel = \"llama-3.3-70b\"\nmax_tokens = 512",
+            "provider = \"openai_compat\"\nbase_url = \"http://localhost:8000/v1\"\n\
+             model = \"qwen\"\nheaders = { \"X-Title\" = \"chace\" }",
+            "provider = \"anthropic\"\nmodel = \"claude-sonnet-4-5\"\n\
+             stop_sequences = [\"\\n}\"]",
+            "provider = \"ollama\"\nmodel = \"qwen2.5-coder:7b\"\nfim = \"qwen\"",
+            "provider = \"llama_cpp\"\ngrammar = \"\"",
+        ] {
+            parse(table).validate("b").unwrap();
+        }
+    }
+
+    #[test]
+    fn test_errors_name_the_key() {
+        let cases = [
+            (
+                "provider = \"ollama\"",
+                "backends.b.model: required by provider ollama",
+            ),
+            (
+                "provider = \"openai_compat\"\nmodel = \"m\"",
+                "backends.b.base_url: required by provider openai_compat",
+            ),
+            (
+                "provider = \"gemini\"\ngrammar = \"root ::= x\"",
+                "backends.b.grammar: not supported by provider gemini",
+            ),
+            (
+                "provider = \"anthropic\"\nmodel = \"m\"\ntemperature = 1.5",
+                "backends.b.temperature: must be between 0 and 1, got 1.5",
+            ),
+            (
+                "provider = \"groq\"\nmax_tokens = 0",
+                "backends.b.max_tokens: must be greater than 0",
+            ),
+            (
+                "provider = \"ollama\"\nmodel = \"m\"\nbase_url = \"localhost\"",
+                "backends.b.base_url: invalid URL \"localhost\"",
+            ),
+            (
+                "provider = \"ollama\"\nmodel = \"m\"\napi_key_file = \"k\"",
+                "backends.b.api_key_file: not supported by provider ollama",
+            ),
+            (
+                "provider = \"groq\"\napi_key_env = \"K\"\n\
+                 api_key_command = \"pass show groq\"",
+                "backends.b.api_key_command: cannot be combined with \
+                 backends.b.api_key_env",
+            ),
+            (
+                "provider = \"llama_cpp\"\ngrammar = \"g\"\ngrammar_file = \"f\"",
+                "backends.b.grammar_file: cannot be combined with \
+                 backends.b.grammar",
+            ),
+        ];
+        for (table, expected) in cases {
+            assert_eq!(validation_error(table), expected);
+        }
+
+        let err = validation_error("provider = \"ollama\"\nmodel = \"m\"\nfim = \"gpt\"");
+        assert!(err.starts_with("backends.b.fim: unknown FIM preset"));
+    }
+
+    #[test]
+    fn test_parse_errors_name_the_key() {
+        let err = toml::from_str::<BackendConfig>("provider = \"olama\"")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown variant `olama`"));
+
+        let err = toml::from_str::<BackendConfig>(
+            "provider = \"ollama\"\nmodle = \"m\"",
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(err.contains("unknown field `modle`"));
+    }
+
+    #[test]
+    fn test_build_reports_missing_key_by_name() {
+        let config = parse(
+            "provider = \"openai_compat\"\nbase_url = \"http://x\"\n\
+             model = \"m\"\napi_key_env = \"CHACE_TEST_UNSET_KEY\"",
+        );
+        let err = config.build().err().unwrap();
+        assert_eq!(err.to_string(), "CHACE_TEST_UNSET_KEY is not set");
+
+        let config = parse("provider = \"ollama\"\nmodel = \"m\"");
+        assert!(config.build().is_ok());
+    }
+
+    #[test]
+    fn test_key_sources() {
+        let config = parse("provider = \"groq\"");
+        assert_eq!(
+            config.key_source(),
+            Some(KeySource::Env("GROQ_API_KEY".into()))
+        );
+
+        let config =
+            parse("provider = \"groq\"\napi_key_command = \"echo gsk-test\"");
+        assert!(config.build().is_ok());
+
+        let config = parse(
+            "provider = \"anthropic\"\nmodel = \"m\"\n\
+             api_key_keyring = \"claude\"",
+        );
+        assert_eq!(
+            config.key_source(),
+            Some(KeySource::Keyring("claude".into()))
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use anyhow::{Result, bail};
+use serde::Deserialize;
+
+/// Sentinel tokens a code model was trained with for fill-in-the-middle.
+/// The prompt is `prefix token, prefix, suffix token, suffix, middle token`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FimTemplate {
+    pub prefix: String,
+    pub suffix: String,
+    pub middle: String,
+    /// Tokens ending the middle, stripped from the output if echoed
+    #[serde(default)]
+    pub stop: Vec<String>,
+}
+
+/// How a backend config selects its FIM template: a preset name or the
+/// tokens themselves
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum FimConfig {
+    Preset(String),
+    Tokens(FimTemplate),
+}
+
+impl FimTemplate {
+    /// Templates of common code model families
+    pub fn preset(name: &str) -> Option<Self> {
+        let (prefix, suffix, middle, stop): (_, _, _, &[&str]) = match name {
+            // Qwen2.5-Coder
+            "qwen" => (
+                "<|fim_prefix|>",
+                "<|fim_suffix|>",
+                "<|fim_middle|>",
+                &["<|endoftext|>", "<|fim_pad|>", "<|file_sep|>"],
+            ),
+            // StarCoder, StarCoder2, Granite
+            "starcoder" => (
+                "<fim_prefix>",
+                "<fim_suffix>",
+                "<fim_middle>",
+                &["<|endoftext|>", "<file_sep>"],
+            ),
+            "codellama" => ("<PRE> ", " <SUF>", " <MID>", &["<EOT>"]),
+            "deepseek" => (
+                "<｜fim▁begin｜>",
+                "<｜fim▁hole｜>",
+                "<｜fim▁end｜>",
+                &["<｜end▁of▁sentence｜>"],
+            ),
+            _ => return None,
+        };
+
+        Some(FimTemplate {
+            prefix: prefix.to_string(),
+            suffix: suffix.to_string(),
+            middle: middle.to_string(),
+            stop: stop.iter().map(|s| s.to_string()).collect(),
+        })
+    }
+
+    /// Builds the prompt asking for the code between `prefix` and `suffix`
+    pub fn prompt(&self, prefix: &str, suffix: &str) -> String {
+        format!(
+            "{}{prefix}{}{suffix}{}",
+            self.prefix, self.suffix, self.middle
+        )
+    }
+
+    /// Stop sequences of a request using the template. Requested ones are
+    /// added to the template's tokens, which must still end the middle.
+    pub fn stop_with(&self, requested: &[String]) -> Vec<String> {
+        [self.stop.as_slice(), requested].concat()
+    }
+
+    /// Cuts the generated middle at the first stop token
+    pub fn clean_middle(&self, output: &str) -> String {
+        let end = self
+            .stop
+            .iter()
+            .filter_map(|stop| output.find(stop.as_str()))
+            .min()
+            .unwrap_or(output.len());
+        output[..end].to_string()
+    }
+}
+
+impl FimConfig {
+    pub fn template(&self) -> Result<FimTemplate> {
+        match self {
+            FimConfig::Preset(name) => match FimTemplate::preset(name) {
+                Some(template) => Ok(template),
+                None => bail!(
+                    "unknown FIM preset {name:?} (expected qwen, starcoder, \
+                     codellama or deepseek)"
+                ),
+            },
+            FimConfig::Tokens(template) => Ok(template.clone()),
+        }
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prompt_layout() {
+        let template = FimTemplate::preset("qwen").unwrap();
+        assert_eq!(
+            template.prompt("fn f() {", "}\n"),
+            "<|fim_prefix|>fn f() {<|fim_suffix|>}\n<|fim_middle|>"
+        );
+        assert_eq!(
+            template.clean_middle("\n    1\n<|endoftext|>garbage"),
+            "\n    1\n"
+        );
+        assert_eq!(
+            template.stop_with(&["\n\n".to_string()]),
+            ["<|endoftext|>", "<|fim_pad|>", "<|file_sep|>", "\n\n"]
+        );
+    }
+
+    #[test]
+    fn test_config_accepts_preset_or_tokens() {
+        #[derive(Deserialize)]
+        struct Backend {
+            fim: FimConfig,
+        }
+
+        let preset: Backend = toml::from_str("fim = \"starcoder\"").unwrap();
+        assert_eq!(preset.fim.template().unwrap().middle, "<fim_middle>");
+
+        let tokens: Backend = toml::from_str(
+            "fim = { prefix = \"[P]\", suffix = \"[S]\", middle = \"[M]\" }",
+        )
+        .unwrap();
+        let template = tokens.fim.template().unwrap();
+        assert_eq!(template.prompt("a", "b"), "[P]a[S]b[M]");
+
+        let unknown: Backend = toml::from_str("fim = \"gpt\"").unwrap();
+        assert!(unknown.fim.template().is_err());
+    }
+}
@@ -1,20 +1,40 @@
-use crate::ai::backend::{LLMBackend, LLMResponse, TokenUsage};
-use crate::ai::helpers::clean_output;
-use anyhow::Result;
+use crate::ai::backend::{
+    GenerationOptions, LLMBackend, LLMResponse, TokenUsage,
+};
+use crate::ai::helpers::{build_user_prompt, clean_output};
+use crate::ai::sse::read_events;
+use anyhow::{Result, bail};
 use async_trait::async_trait;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 
+pub const DEFAULT_GEMINI_URL: &str =
+    "https://generativelanguage.googleapis.com";
+
 /// Gemini backend implementation
 pub struct GeminiBackend {
+    pub base_url: String,
     pub api_key: String,
     pub model: String,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
 }
 
 // Request body
 #[derive(Serialize)]
 struct GeminiRequest<'a> {
     contents: Vec<GeminiContent<'a>>,
+    #[serde(rename = "generationConfig")]
+    generation_config: GenerationConfig<'a>,
+}
+#[derive(Serialize)]
+struct GenerationConfig<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(rename = "maxOutputTokens", skip_serializing_if = "Option::is_none")]
+    max_output_tokens: Option<u32>,
+    #[serde(rename = "stopSequences", skip_serializing_if = "<[String]>::is_empty")]
+    stop_sequences: &'a [String],
 }
 #[derive(Serialize)]
 struct GeminiContent<'a> {
@@ -42,12 +62,23 @@ struct GeminiResponse {
     #[serde(rename = "usageMetadata")] // Gemini uses camelCase
     usage_metadata: GeminiUsage,
 }
+/// An event of `streamGenerateContent`, the last one carrying the usage
+#[derive(Deserialize)]
+struct GeminiStreamChunk {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    #[serde(rename = "usageMetadata")]
+    usage_metadata: Option<GeminiUsage>,
+}
 #[derive(Deserialize)]
 struct GeminiCandidate {
+    // The closing chunk of a stream may only have a finish reason
+    #[serde(default)]
     content: GeminiCandidateContent,
 }
-#[derive(Deserialize)]
+#[derive(Deserialize, Default)]
 struct GeminiCandidateContent {
+    #[serde(default)]
     parts: Vec<GeminiPartResponse>,
 }
 #[derive(Deserialize)]
@@ -61,8 +92,10 @@ impl LLMBackend for GeminiBackend {
         &self,
         signature: &str,
         doc_comment: Option<&str>,
+        instructions: Option<&str>,
         context_snippets: Option<&[String]>,
         language: &str,
+        options: &GenerationOptions,
     ) -> Result<LLMResponse> {
         let client = Client::new();
 
@@ -70,43 +103,43 @@ impl LLMBackend for GeminiBackend {
             "You are an AI {} code generator.\n\
              Complete only the body of the function.\n\
              Do NOT add explanations or markdown.\n\
+             Follow the implementation instructions when present.\n\
              Respond only with valid {} code inside the braces.",
             language, language
         );
 
-        let mut user_prompt = String::new();
-
-        if let Some(snippets) = context_snippets {
-            if !snippets.is_empty() {
-                user_prompt.push_str("Context code for reference:\n");
-                for snippet in snippets {
-                    user_prompt.push_str("---\n");
-                    user_prompt.push_str(snippet);
-                    user_prompt.push_str("\n---\n\n");
-                }
-            }
-        }
-
-        user_prompt.push_str(&format!(
-            "{}\n{} {{",
-            doc_comment.unwrap_or(""),
-            signature
-        ));
+        let user_prompt = build_user_prompt(
+            signature,
+            doc_comment,
+            instructions,
+            context_snippets,
+        );
 
         let full_prompt = format!("{}\n{}", system_prompt, user_prompt);
 
-        let url = format!(
-            "https://generativelanguage.googleapis.com/v1beta/models/{}:generateContent",
-            self.model
-        );
-
         let request_body = GeminiRequest {
             contents: vec![GeminiContent {
                 role: "user",
                 parts: vec![GeminiPart { text: &full_prompt }],
             }],
+            generation_config: GenerationConfig {
+                temperature: options.temperature.or(self.temperature),
+                max_output_tokens: options.max_tokens.or(self.max_tokens),
+                stop_sequences: &options.stop,
+            },
         };
 
+        let method = match options.on_delta {
+            Some(_) => "streamGenerateContent?alt=sse",
+            None => "generateContent",
+        };
+        let url = format!(
+            "{}/v1beta/models/{}:{}",
+            self.base_url.trim_end_matches('/'),
+            model_segment(options.model(&self.model))?,
+            method
+        );
+
         let resp = client
             .post(&url)
             .header("x-goog-api-key", &self.api_key)
@@ -114,24 +147,176 @@ impl LLMBackend for GeminiBackend {
             .json(&request_body)
             .send()
             .await?
-            .error_for_status()?
-            .json::<GeminiResponse>()
+            .error_for_status()?;
+
+        if let Some(on_delta) = &options.on_delta {
+            let mut output = String::new();
+            let mut usage = None;
+            read_events(resp, |data| {
+                let chunk: GeminiStreamChunk = serde_json::from_str(data)?;
+                if let Some(candidate) = chunk.candidates.first() {
+                    for part in &candidate.content.parts {
+                        on_delta(&part.text);
+                        output.push_str(&part.text);
+                    }
+                }
+                if let Some(u) = chunk.usage_metadata {
+                    usage = Some(usage_from(u));
+                }
+                Ok(())
+            })
             .await?;
 
-        let output = resp
+            return Ok(LLMResponse { body: clean_output(&output), usage });
+        }
+
+        let resp = resp.json::<GeminiResponse>().await?;
+
+        // a candidate may come in several parts, as it does when streamed
+        let output: String = resp
             .candidates
-            .get(0)
-            .and_then(|c| c.content.parts.get(0))
-            .map(|p| p.text.clone())
+            .first()
+            .map(|c| c.content.parts.iter().map(|p| p.text.as_str()).collect())
             .unwrap_or_default();
 
         Ok(LLMResponse {
             body: clean_output(&output),
-            usage: Some(TokenUsage {
-                prompt_tokens: resp.usage_metadata.prompt_token_count,
-                completion_tokens: resp.usage_metadata.candidates_token_count,
-                total_tokens: resp.usage_metadata.total_token_count,
-            }),
+            usage: Some(usage_from(resp.usage_metadata)),
         })
     }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+}
+
+//---------------------- Backend specific helpers -----------------------------
+
+fn usage_from(usage: GeminiUsage) -> TokenUsage {
+    TokenUsage {
+        prompt_tokens: usage.prompt_token_count,
+        completion_tokens: usage.candidates_token_count,
+        total_tokens: usage.total_token_count,
+    }
+}
+
+/// The model as a segment of the URL path, which any other character could
+/// escape from
+fn model_segment(model: &str) -> Result<&str> {
+    let valid =
+        |c: char| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-');
+    if model.is_empty() || !model.chars().all(valid) {
+        bail!("Invalid Gemini model name: {model:?}");
+    }
+    Ok(model)
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock_server::MockServer;
+    use std::sync::{Arc, Mutex};
+
+    fn backend(base_url: &str) -> GeminiBackend {
+        GeminiBackend {
+            base_url: format!("{base_url}/"),
+            api_key: "gemini-test".to_string(),
+            model: "gemini-2.5-flash".to_string(),
+            temperature: None,
+            max_tokens: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_parts_are_joined() {
+        let server = MockServer::start(
+            200,
+            r#"{
+                "candidates": [{"content": {"parts": [
+                    {"text": "```rust\na"}, {"text": " + b\n```"}
+                ]}}],
+                "usageMetadata": {"promptTokenCount": 4,
+                    "candidatesTokenCount": 3, "totalTokenCount": 7}
+            }"#,
+        )
+        .await;
+
+        let resp = backend(&server.url)
+            .generate_function(
+                "fn f()",
+                None,
+                None,
+                None,
+                "rust",
+                &GenerationOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.body, "a + b");
+        assert_eq!(resp.usage.unwrap().total_tokens, 7);
+        let request = &server.requests()[0];
+        assert_eq!(
+            request.path,
+            "/v1beta/models/gemini-2.5-flash:generateContent"
+        );
+        assert_eq!(request.headers["x-goog-api-key"], "gemini-test");
+    }
+
+    #[tokio::test]
+    async fn test_streamed_generation() {
+        let server = MockServer::start(
+            200,
+            concat!(
+                "data: {\"candidates\":[{\"content\":{\"parts\":",
+                "[{\"text\":\"```rust\\na\"}]}}]}\n\n",
+                "data: {\"candidates\":[{\"content\":{\"parts\":",
+                "[{\"text\":\" + b\"},{\"text\":\"\\n```\"}]}}]}\n\n",
+                "data: {\"candidates\":[{\"finishReason\":\"STOP\"}],",
+                "\"usageMetadata\":{\"promptTokenCount\":4,",
+                "\"candidatesTokenCount\":3,\"totalTokenCount\":7}}\n\n",
+            ),
+        )
+        .await;
+
+        let deltas = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&deltas);
+        let options = GenerationOptions {
+            on_delta: Some(Arc::new(move |delta| {
+                sink.lock().unwrap().push(delta.to_string())
+            })),
+            ..Default::default()
+        };
+
+        let resp = backend(&server.url)
+            .generate_function("fn f()", None, None, None, "rust", &options)
+            .await
+            .unwrap();
+
+        // Chunks are raw, the final body is cleaned
+        let deltas = deltas.lock().unwrap();
+        assert_eq!(*deltas, vec!["```rust\na", " + b", "\n```"]);
+        assert_eq!(resp.body, "a + b");
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 4);
+        assert_eq!(usage.completion_tokens, 3);
+        assert_eq!(usage.total_tokens, 7);
+
+        let request = &server.requests()[0];
+        assert_eq!(
+            request.path,
+            "/v1beta/models/gemini-2.5-flash:streamGenerateContent?alt=sse"
+        );
+    }
+
+    #[test]
+    fn test_model_names_stay_in_their_segment() {
+        let model = "gemini-2.5-flash";
+        assert_eq!(model_segment(model).unwrap(), model);
+        for model in ["", "../files", "a:b", "a?key=x", "a/b", "a b"] {
+            assert!(model_segment(model).is_err(), "{model}");
+        }
+    }
 }
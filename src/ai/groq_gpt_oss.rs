@@ -1,5 +1,8 @@
-use crate::ai::backend::{LLMBackend, LLMResponse, TokenUsage};
-use crate::ai::helpers::clean_output;
+use crate::ai::backend::{
+    GenerationOptions, LLMBackend, LLMResponse, TokenUsage,
+};
+use crate::ai::helpers::{build_user_prompt, clean_output};
+use crate::ai::sse::read_chat_stream;
 use anyhow::Result;
 use async_trait::async_trait;
 use reqwest::Client;
@@ -8,6 +11,8 @@ use serde::{Deserialize, Serialize};
 pub struct GGPTOSSBackend {
     pub api_key: String,
     pub model: String,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
 }
 
 #[derive(Serialize)]
@@ -20,6 +25,14 @@ struct Message<'a> {
 struct GROQRequest<'a> {
     model: &'a str,
     messages: Vec<Message<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop: &'a [String],
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Deserialize)]
@@ -51,8 +64,10 @@ impl LLMBackend for GGPTOSSBackend {
         &self,
         signature: &str,
         doc_comment: Option<&str>,
+        instructions: Option<&str>,
         context_snippets: Option<&[String]>,
         language: &str,
+        options: &GenerationOptions,
     ) -> Result<LLMResponse> {
         let client = Client::new();
 
@@ -67,53 +82,51 @@ impl LLMBackend for GGPTOSSBackend {
             4.  The output must be the raw, executable code for the function body.\n\
             ---\n\n\
             ### Input Context:\n\
-            You will be provided with the function's signature, documentation (if available), and context snippets (if available) from the source file. Use these context snippets to understand the project structure and common utility functions.",
+            You will be provided with the function's signature, documentation (if available), implementation instructions (if available), and context snippets (if available) from the source file. Use these context snippets to understand the project structure and common utility functions, and follow the implementation instructions when present.",
             language, language
         );
 
-        let mut user_prompt = String::new();
-
-        if let Some(snippets) = context_snippets {
-            if !snippets.is_empty() {
-                user_prompt.push_str("Context code for reference:\n");
-                for snippet in snippets {
-                    user_prompt.push_str("---\n");
-                    user_prompt.push_str(snippet);
-                    user_prompt.push_str("\n---\n\n");
-                }
-            }
-        }
-
-        user_prompt.push_str(&format!(
-            "{}\n{} {{",
-            doc_comment.unwrap_or(""),
-            signature
-        ));
+        let user_prompt = build_user_prompt(
+            signature,
+            doc_comment,
+            instructions,
+            context_snippets,
+        );
 
-        let url = format!("https://api.groq.com/openai/v1/chat/completions",);
+        let url = "https://api.groq.com/openai/v1/chat/completions";
 
         let request_body = GROQRequest {
-            model: &self.model,
+            model: options.model(&self.model),
             messages: vec![
                 Message { role: "system", content: &system_prompt },
                 Message { role: "user", content: &user_prompt },
             ],
+            temperature: options.temperature.or(self.temperature),
+            max_tokens: options.max_tokens.or(self.max_tokens),
+            stop: &options.stop,
+            stream: options.on_delta.is_some(),
         };
 
         let resp = client
-            .post(&url)
+            .post(url)
             .header("Content-Type", "application/json")
             .header("Authorization", format!("Bearer {}", self.api_key))
             .json(&request_body)
             .send()
             .await?
-            .error_for_status()?
-            .json::<GGPTOSSResponse>()
-            .await?;
+            .error_for_status()?;
+
+        // Groq reports the usage of a stream in its last chunk
+        if let Some(on_delta) = &options.on_delta {
+            let (output, usage) = read_chat_stream(resp, on_delta).await?;
+            return Ok(LLMResponse { body: clean_output(&output), usage });
+        }
+
+        let resp = resp.json::<GGPTOSSResponse>().await?;
 
         let output = resp
             .choices
-            .get(0)
+            .first()
             .map(|c| c.message.content.clone())
             .unwrap_or_default();
 
@@ -126,4 +139,8 @@ impl LLMBackend for GGPTOSSBackend {
             }),
         })
     }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
 }
@@ -1,3 +1,51 @@
+/// System prompt for chat backends that take it as a separate message
+pub fn system_prompt(language: &str) -> String {
+    format!(
+        "You are an AI {} code generator.\n\
+         Complete only the body of the function, without the braces.\n\
+         Do NOT add explanations or markdown.\n\
+         Follow the implementation instructions when present.\n\
+         Respond only with valid {} code.",
+        language, language
+    )
+}
+
+/// Builds the user prompt from the context snippets, the doc comment, the
+/// instructions found in the body and the signature
+pub fn build_user_prompt(
+    signature: &str,
+    doc_comment: Option<&str>,
+    instructions: Option<&str>,
+    context_snippets: Option<&[String]>,
+) -> String {
+    let mut user_prompt = String::new();
+
+    if let Some(snippets) = context_snippets
+        && !snippets.is_empty()
+    {
+        user_prompt.push_str("Context code for reference:\n");
+        for snippet in snippets {
+            user_prompt.push_str("---\n");
+            user_prompt.push_str(snippet);
+            user_prompt.push_str("\n---\n\n");
+        }
+    }
+
+    if let Some(instructions) = instructions {
+        user_prompt.push_str("Implementation instructions:\n");
+        user_prompt.push_str(instructions);
+        user_prompt.push_str("\n\n");
+    }
+
+    user_prompt.push_str(&format!(
+        "{}\n{} {{",
+        doc_comment.unwrap_or(""),
+        signature
+    ));
+
+    user_prompt
+}
+
 /// Cleanup of markdown/code fences, extra text
 pub fn clean_output(output: &str) -> String {
     let mut out = output.trim();
@@ -0,0 +1,231 @@
+use anyhow::{Context, Result, bail};
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+/// Keyring service the entries of `api_key_keyring` are stored under
+const KEYRING_SERVICE: &str = "chace";
+
+/// How long a key command or the keyring may take, leaving time to answer
+/// a passphrase prompt
+const KEY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Where the API key of a backend is read from. Errors name the source but
+/// never include what was read, since they end up in logs and responses.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KeySource {
+    /// Environment variable
+    Env(String),
+    /// File holding only the key, e.g. a mounted secret
+    File(PathBuf),
+    /// Shell command printing the key on its first line, e.g. `pass show groq`
+    Command(String),
+    /// Secret Service entry of user `name` in the `chace` service
+    Keyring(String),
+}
+
+impl KeySource {
+    pub fn read(&self) -> Result<String> {
+        let key = match self {
+            KeySource::Env(var) => match std::env::var(var) {
+                Ok(key) => key,
+                Err(_) => bail!("{var} is not set"),
+            },
+            KeySource::File(path) => std::fs::read_to_string(path)
+                .with_context(|| format!("reading {}", path.display()))?
+                .trim()
+                .to_string(),
+            KeySource::Command(command) => {
+                run_key_command(command, KEY_TIMEOUT)?
+            }
+            KeySource::Keyring(user) => read_keyring(user, KEY_TIMEOUT)?,
+        };
+
+        if key.is_empty() {
+            bail!("{self} is empty");
+        }
+        Ok(key)
+    }
+}
+
+impl std::fmt::Display for KeySource {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KeySource::Env(var) => write!(f, "{var}"),
+            KeySource::File(path) => write!(f, "{}", path.display()),
+            KeySource::Command(command) => write!(f, "output of `{command}`"),
+            KeySource::Keyring(user) => {
+                write!(f, "keyring entry {KEYRING_SERVICE}/{user}")
+            }
+        }
+    }
+}
+
+fn run_key_command(command: &str, timeout: Duration) -> Result<String> {
+    // stderr is left to the server log so prompts and failures show up.
+    // The command gets a process group of its own, so a timeout also kills
+    // what it started, which may hold stdout open.
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .process_group(0)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .with_context(|| format!("running `{command}`"))?;
+
+    // read on a thread of its own, so a hung command can be killed
+    let mut stdout = child.stdout.take().expect("stdout is piped");
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut output = Vec::new();
+        let _ = tx.send(stdout.read_to_end(&mut output).map(|_| output));
+    });
+
+    let output = match rx.recv_timeout(timeout) {
+        Ok(output) => output,
+        Err(_) => {
+            // SAFETY: plain system call, the group id is the child's pid
+            unsafe { libc::killpg(child.id() as libc::pid_t, libc::SIGKILL) };
+            let _ = child.wait();
+            bail!("`{command}` timed out after {timeout:?}");
+        }
+    };
+    let status = child.wait()?;
+
+    // whatever was printed stays out of the error, it may be the key
+    if !status.success() {
+        bail!("`{command}` failed ({status})");
+    }
+    let Ok(stdout) = String::from_utf8(output?) else {
+        bail!("output of `{command}` is not UTF-8");
+    };
+
+    Ok(stdout.lines().next().unwrap_or_default().trim().to_string())
+}
+
+fn read_keyring(user: &str, timeout: Duration) -> Result<String> {
+    let entry = keyring::Entry::new(KEYRING_SERVICE, user)
+        .with_context(|| format!("keyring entry {KEYRING_SERVICE}/{user}"))?;
+
+    // The Secret Service client blocks on a runtime of its own, which cannot
+    // be started from a thread already driving the server's. A locked
+    // keyring may never answer, the thread is then left behind.
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = tx.send(entry.get_password());
+    });
+
+    match rx.recv_timeout(timeout) {
+        Ok(Ok(key)) => Ok(key),
+        Ok(Err(keyring::Error::NoEntry)) => {
+            bail!("no keyring entry {KEYRING_SERVICE}/{user}")
+        }
+        // the Debug output of this error holds the raw bytes
+        Ok(Err(keyring::Error::BadEncoding(_))) => {
+            bail!("keyring entry {KEYRING_SERVICE}/{user} is not UTF-8")
+        }
+        Ok(Err(e)) => {
+            bail!("reading keyring entry {KEYRING_SERVICE}/{user}: {e}")
+        }
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            bail!("keyring entry {KEYRING_SERVICE}/{user} timed out")
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => {
+            bail!("reading keyring entry {KEYRING_SERVICE}/{user} panicked")
+        }
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fresh directory for the files of one test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("chace_{name}_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_key_is_trimmed() {
+        let dir = test_dir("key_file");
+        let path = dir.join("key");
+        std::fs::write(&path, "gsk-from-file\n").unwrap();
+        let source = KeySource::File(path.clone());
+        assert_eq!(source.read().unwrap(), "gsk-from-file");
+
+        std::fs::write(&path, "\n").unwrap();
+        let err = KeySource::File(path.clone()).read().unwrap_err();
+        assert_eq!(err.to_string(), format!("{} is empty", path.display()));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_command_key_is_its_first_line() {
+        let source =
+            KeySource::Command("printf 'gsk-from-pass\\nlogin: me\\n'".into());
+        assert_eq!(source.read().unwrap(), "gsk-from-pass");
+    }
+
+    #[test]
+    fn test_hung_command_times_out() {
+        let timeout = Duration::from_millis(100);
+        let err = run_key_command("sleep 10", timeout).unwrap_err();
+        assert_eq!(err.to_string(), "`sleep 10` timed out after 100ms");
+    }
+
+    #[test]
+    fn test_timeout_kills_background_processes() {
+        let dir = test_dir("key_background");
+        let pid_file = dir.join("pid");
+        // the background sleep inherits stdout, keeping the pipe open
+        let command =
+            format!("sleep 30 & echo $! > {}; wait", pid_file.display());
+
+        let started = std::time::Instant::now();
+        let timeout = Duration::from_millis(200);
+        assert!(run_key_command(&command, timeout).is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        // once it is gone the reader thread sees the end of the output
+        let pid = std::fs::read_to_string(&pid_file).unwrap();
+        let stat = PathBuf::from(format!("/proc/{}/stat", pid.trim()));
+        let deadline = std::time::Instant::now() + Duration::from_secs(5);
+        while std::fs::read_to_string(&stat)
+            .is_ok_and(|stat| !stat.contains(") Z "))
+        {
+            assert!(std::time::Instant::now() < deadline, "sleep still runs");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_errors_never_contain_the_key() {
+        let dir = test_dir("key_leak");
+        let path = dir.join("key");
+        std::fs::write(&path, "gsk-secret\n").unwrap();
+        let command = format!("cat {}; exit 3", path.display());
+
+        let err = KeySource::Command(command.clone()).read().unwrap_err();
+        let err = format!("{err:#}");
+        assert!(!err.contains("gsk-secret"));
+        assert_eq!(err, format!("`{command}` failed (exit status: 3)"));
+
+        let source = KeySource::Env("CHACE_TEST_UNSET_KEY".into());
+        assert_eq!(
+            source.read().unwrap_err().to_string(),
+            "CHACE_TEST_UNSET_KEY is not set"
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
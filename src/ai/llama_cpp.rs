@@ -0,0 +1,358 @@
+use crate::ai::backend::{
+    GenerationOptions, LLMBackend, LLMResponse, TokenUsage,
+};
+use crate::ai::fim::FimTemplate;
+use crate::ai::helpers::{build_user_prompt, clean_output};
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_LLAMA_CPP_URL: &str = "http://localhost:8080";
+
+/// GBNF grammar accepting text with balanced braces, ending at the first
+/// unmatched `}`, taken as the brace closing the function, so no prose can
+/// follow the body. Braces inside strings and comments are counted like any
+/// other. Backticks stay allowed for template literals, so a fenced body is
+/// still possible and is cleaned up like the chat backends' output.
+pub const BRACE_BODY_GRAMMAR: &str = r#"root  ::= body "}"
+body  ::= (chunk | group)*
+group ::= "{" body "}"
+chunk ::= [^{}]
+"#;
+
+/// Backend for a local llama.cpp `llama-server`, using its native
+/// `/completion` endpoint. Runs offline, on CPU if need be. The server
+/// serves a single model, so a requested model is ignored.
+pub struct LlamaCppBackend {
+    pub base_url: String,
+    pub n_predict: u32,
+    /// GBNF grammar constraining the body, [`BRACE_BODY_GRAMMAR`] for bodies
+    /// closed by a brace when unset
+    pub grammar: Option<String>,
+    /// Sends no grammar at all, even the default one
+    pub unconstrained: bool,
+    pub fim: Option<FimTemplate>,
+    pub temperature: Option<f32>,
+}
+
+#[derive(Serialize)]
+struct CompletionRequest<'a> {
+    prompt: &'a str,
+    n_predict: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    grammar: Option<&'a str>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop: &'a [String],
+    cache_prompt: bool,
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct CompletionResponse {
+    content: String,
+    #[serde(default)]
+    tokens_evaluated: u32,
+    #[serde(default)]
+    tokens_predicted: u32,
+}
+
+impl LlamaCppBackend {
+    /// Grammar to constrain the body with, if any
+    fn grammar_for(&self, options: &GenerationOptions) -> Option<&str> {
+        if self.unconstrained {
+            return None;
+        }
+        match &self.grammar {
+            Some(grammar) => Some(grammar),
+            // Bodies delimited otherwise have no closing brace to stop at
+            None if !options.braced_body => None,
+            None => Some(BRACE_BODY_GRAMMAR),
+        }
+    }
+
+    async fn complete(
+        &self,
+        prompt: &str,
+        grammar: Option<&str>,
+        stop: &[String],
+        options: &GenerationOptions,
+    ) -> Result<CompletionResponse> {
+        let client = Client::new();
+
+        let url = format!("{}/completion", self.base_url.trim_end_matches('/'));
+
+        let request_body = CompletionRequest {
+            prompt,
+            n_predict: options.max_tokens.unwrap_or(self.n_predict),
+            temperature: options.temperature.or(self.temperature),
+            grammar,
+            stop,
+            cache_prompt: true,
+            stream: false,
+        };
+
+        let resp = client
+            .post(&url)
+            .json(&request_body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<CompletionResponse>()
+            .await?;
+
+        Ok(resp)
+    }
+}
+
+#[async_trait]
+impl LLMBackend for LlamaCppBackend {
+    async fn generate_function(
+        &self,
+        signature: &str,
+        doc_comment: Option<&str>,
+        instructions: Option<&str>,
+        context_snippets: Option<&[String]>,
+        _language: &str,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        // A raw completion: the model continues right after the opening brace
+        let prompt = build_user_prompt(
+            signature,
+            doc_comment,
+            instructions,
+            context_snippets,
+        );
+
+        let grammar = self.grammar_for(options);
+        let resp =
+            self.complete(&prompt, grammar, &options.stop, options).await?;
+
+        // The grammar guarantees the closing brace, which is not ours to insert
+        let body = match grammar {
+            Some(BRACE_BODY_GRAMMAR) => {
+                let Some(body) = resp.content.trim_end().strip_suffix('}')
+                else {
+                    bail!("llama.cpp output ended before the closing brace");
+                };
+                body
+            }
+            _ => &resp.content,
+        };
+
+        Ok(LLMResponse {
+            body: clean_output(body),
+            usage: Some(usage(&resp)),
+        })
+    }
+
+    fn supports_fim(&self) -> bool {
+        self.fim.is_some()
+    }
+
+    async fn fill_in_middle(
+        &self,
+        prefix: &str,
+        suffix: &str,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        let Some(template) = &self.fim else {
+            bail!("no FIM template configured");
+        };
+
+        let prompt = template.prompt(prefix, suffix);
+        let stop = template.stop_with(&options.stop);
+        let resp = self.complete(&prompt, None, &stop, options).await?;
+
+        Ok(LLMResponse {
+            body: template.clean_middle(&resp.content),
+            usage: Some(usage(&resp)),
+        })
+    }
+}
+
+//---------------------- Backend specific helpers -----------------------------
+
+fn usage(resp: &CompletionResponse) -> TokenUsage {
+    TokenUsage {
+        prompt_tokens: resp.tokens_evaluated,
+        completion_tokens: resp.tokens_predicted,
+        total_tokens: resp.tokens_evaluated + resp.tokens_predicted,
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock_server::MockServer;
+
+    const RESPONSE: &str = r#"{
+        "content": "\n    if a > b { a } else { b }\n}",
+        "stop": true,
+        "stopped_eos": false,
+        "stopped_word": false,
+        "tokens_evaluated": 21,
+        "tokens_predicted": 14,
+        "truncated": false
+    }"#;
+
+    fn backend(base_url: &str) -> LlamaCppBackend {
+        LlamaCppBackend {
+            base_url: base_url.to_string(),
+            n_predict: 256,
+            grammar: None,
+            unconstrained: false,
+            fim: None,
+            temperature: None,
+        }
+    }
+
+    fn braced() -> GenerationOptions {
+        GenerationOptions {
+            braced_body: true,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_completion_with_default_grammar() {
+        let server = MockServer::start(200, RESPONSE).await;
+
+        let resp = backend(&server.url)
+            .generate_function(
+                "fn max(a: u32, b: u32) -> u32",
+                None,
+                None,
+                None,
+                "rust",
+                &braced(),
+            )
+            .await
+            .unwrap();
+
+        // Inner braces stay, the function's closing brace goes
+        assert_eq!(resp.body, "if a > b { a } else { b }");
+        assert_eq!(resp.usage.unwrap().total_tokens, 35);
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/completion");
+        assert_eq!(request.body["grammar"], BRACE_BODY_GRAMMAR);
+        assert_eq!(request.body["n_predict"], 256);
+        assert_eq!(request.body["prompt"], "\nfn max(a: u32, b: u32) -> u32 {");
+    }
+
+    #[tokio::test]
+    async fn test_fenced_output_is_cleaned() {
+        let server = MockServer::start(
+            200,
+            r#"{"content": "```rust\n    a.max(b)\n```\n}"}"#,
+        )
+        .await;
+
+        let resp = backend(&server.url)
+            .generate_function(
+                "fn max(a: u32, b: u32) -> u32",
+                None,
+                None,
+                None,
+                "rust",
+                &braced(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.body, "a.max(b)");
+    }
+
+    #[tokio::test]
+    async fn test_truncated_output_is_an_error() {
+        let server = MockServer::start(
+            200,
+            r#"{"content": "\n    if a > b {", "tokens_predicted": 256}"#,
+        )
+        .await;
+
+        let err = backend(&server.url)
+            .generate_function(
+                "fn max(a: u32, b: u32) -> u32",
+                None,
+                None,
+                None,
+                "rust",
+                &braced(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "llama.cpp output ended before the closing brace"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unbraced_and_disabled_grammar_are_unconstrained() {
+        let server =
+            MockServer::start(200, r#"{"content": "return max(a, b)"}"#).await;
+        let resp = backend(&server.url)
+            .generate_function(
+                "def max(a, b):",
+                None,
+                None,
+                None,
+                "python",
+                &GenerationOptions::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(resp.body, "return max(a, b)");
+        assert!(server.requests()[0].body.get("grammar").is_none());
+
+        let mut backend = backend(&server.url);
+        backend.unconstrained = true;
+        assert_eq!(backend.grammar_for(&braced()), None);
+    }
+
+    #[tokio::test]
+    async fn test_fill_in_middle_sends_stop_tokens() {
+        let server = MockServer::start(
+            200,
+            r#"{"content": "\n    a + b\n", "tokens_evaluated": 9, "tokens_predicted": 4}"#,
+        )
+        .await;
+        let mut backend = backend(&server.url);
+        backend.fim = FimTemplate::preset("codellama");
+
+        let resp = backend
+            .fill_in_middle("fn f() {", "}", &GenerationOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(resp.body, "\n    a + b\n");
+
+        let request = &server.requests()[0];
+        assert_eq!(request.body["prompt"], "<PRE> fn f() { <SUF>} <MID>");
+        assert_eq!(request.body["stop"][0], "<EOT>");
+        assert!(request.body.get("grammar").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_request_stop_adds_to_fim_stop() {
+        let server = MockServer::start(200, r#"{"content": "a + b"}"#).await;
+        let mut backend = backend(&server.url);
+        backend.fim = FimTemplate::preset("codellama");
+
+        let options = GenerationOptions {
+            max_tokens: Some(64),
+            stop: vec!["\n\n".to_string()],
+            ..Default::default()
+        };
+        backend.fill_in_middle("fn f() {", "}", &options).await.unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.body["n_predict"], 64);
+        assert_eq!(request.body["stop"][0], "<EOT>");
+        assert_eq!(request.body["stop"][1], "\n\n");
+    }
+}
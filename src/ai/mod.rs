@@ -1,4 +1,15 @@
+pub mod anthropic;
 pub mod backend;
+pub mod config;
+pub mod fim;
 pub mod helpers;
+pub mod keys;
 pub mod gemini;
 pub mod groq_gpt_oss;
+pub mod llama_cpp;
+#[cfg(test)]
+mod mock_server;
+pub mod ollama;
+pub mod openai_compat;
+pub mod registry;
+pub mod sse;
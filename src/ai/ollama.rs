@@ -0,0 +1,300 @@
+use crate::ai::backend::{
+    GenerationOptions, LLMBackend, LLMResponse, TokenUsage,
+};
+use crate::ai::fim::FimTemplate;
+use crate::ai::helpers::{build_user_prompt, clean_output, system_prompt};
+use anyhow::{Result, bail};
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+/// Default address of a local Ollama server
+pub const DEFAULT_OLLAMA_URL: &str = "http://localhost:11434";
+
+/// Backend for a local Ollama server, using its `/api/chat` endpoint, or
+/// `/api/generate` for fill-in-the-middle. Needs no API key, so code never
+/// leaves the machine.
+pub struct OllamaBackend {
+    pub base_url: String,
+    pub model: String,
+    /// Enables fill-in-the-middle prompts with these tokens
+    pub fim: Option<FimTemplate>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<Message<'a>>,
+    stream: bool,
+    options: Options<'a>,
+}
+
+#[derive(Serialize)]
+struct Options<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    message: MessageResponse,
+    // Absent when the prompt was served from the cache
+    #[serde(default)]
+    prompt_eval_count: u32,
+    #[serde(default)]
+    eval_count: u32,
+}
+
+#[derive(Deserialize)]
+struct MessageResponse {
+    content: String,
+}
+
+#[derive(Serialize)]
+struct GenerateRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    /// Sends the prompt as is, bypassing the model's chat template
+    raw: bool,
+    stream: bool,
+    options: Options<'a>,
+}
+
+#[derive(Deserialize)]
+struct GenerateResponse {
+    response: String,
+    #[serde(default)]
+    prompt_eval_count: u32,
+    #[serde(default)]
+    eval_count: u32,
+}
+
+impl OllamaBackend {
+    fn options<'a>(&self, options: &'a GenerationOptions) -> Options<'a> {
+        Options {
+            temperature: options.temperature.or(self.temperature),
+            num_predict: options.max_tokens.or(self.max_tokens),
+            stop: &options.stop,
+        }
+    }
+}
+
+#[async_trait]
+impl LLMBackend for OllamaBackend {
+    async fn generate_function(
+        &self,
+        signature: &str,
+        doc_comment: Option<&str>,
+        instructions: Option<&str>,
+        context_snippets: Option<&[String]>,
+        language: &str,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        let client = Client::new();
+
+        let system_prompt = system_prompt(language);
+
+        let user_prompt = build_user_prompt(
+            signature,
+            doc_comment,
+            instructions,
+            context_snippets,
+        );
+
+        let url = format!("{}/api/chat", self.base_url.trim_end_matches('/'));
+
+        let request_body = ChatRequest {
+            model: options.model(&self.model),
+            messages: vec![
+                Message { role: "system", content: &system_prompt },
+                Message { role: "user", content: &user_prompt },
+            ],
+            stream: false,
+            options: self.options(options),
+        };
+
+        let resp = client
+            .post(&url)
+            .json(&request_body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<ChatResponse>()
+            .await?;
+
+        Ok(LLMResponse {
+            body: clean_output(&resp.message.content),
+            usage: Some(TokenUsage {
+                prompt_tokens: resp.prompt_eval_count,
+                completion_tokens: resp.eval_count,
+                total_tokens: resp.prompt_eval_count + resp.eval_count,
+            }),
+        })
+    }
+
+    fn supports_fim(&self) -> bool {
+        self.fim.is_some()
+    }
+
+    async fn fill_in_middle(
+        &self,
+        prefix: &str,
+        suffix: &str,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        let Some(template) = &self.fim else {
+            bail!("no FIM template configured for {}", self.model);
+        };
+        let client = Client::new();
+
+        let url =
+            format!("{}/api/generate", self.base_url.trim_end_matches('/'));
+
+        let stop = template.stop_with(&options.stop);
+        let request_body = GenerateRequest {
+            model: options.model(&self.model),
+            prompt: &template.prompt(prefix, suffix),
+            raw: true,
+            stream: false,
+            options: Options {
+                stop: &stop,
+                ..self.options(options)
+            },
+        };
+
+        let resp = client
+            .post(&url)
+            .json(&request_body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<GenerateResponse>()
+            .await?;
+
+        Ok(LLMResponse {
+            body: template.clean_middle(&resp.response),
+            usage: Some(TokenUsage {
+                prompt_tokens: resp.prompt_eval_count,
+                completion_tokens: resp.eval_count,
+                total_tokens: resp.prompt_eval_count + resp.eval_count,
+            }),
+        })
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock_server::MockServer;
+
+    const RESPONSE: &str = r#"{
+        "model": "qwen2.5-coder:7b",
+        "created_at": "2025-01-01T00:00:00Z",
+        "message": {"role": "assistant", "content": "    a + b\n"},
+        "done_reason": "stop",
+        "done": true,
+        "total_duration": 5191566416,
+        "prompt_eval_count": 26,
+        "eval_count": 4
+    }"#;
+
+    #[tokio::test]
+    async fn test_chat_request_and_usage() {
+        let server = MockServer::start(200, RESPONSE).await;
+        let backend = OllamaBackend {
+            base_url: format!("{}/", server.url),
+            model: "qwen2.5-coder:7b".to_string(),
+            fim: None,
+            temperature: Some(0.5),
+            max_tokens: None,
+        };
+
+        let resp = backend
+            .generate_function(
+                "fn add(a: u32, b: u32) -> u32",
+                None,
+                None,
+                None,
+                "rust",
+                &GenerationOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.body, "a + b");
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 26);
+        assert_eq!(usage.completion_tokens, 4);
+        assert_eq!(usage.total_tokens, 30);
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/api/chat");
+        assert!(!request.headers.contains_key("authorization"));
+        assert_eq!(request.body["model"], "qwen2.5-coder:7b");
+        assert_eq!(request.body["stream"], false);
+        assert_eq!(request.body["options"]["temperature"], 0.5);
+        assert!(request.body["options"].get("num_predict").is_none());
+        assert_eq!(request.body["messages"][1]["role"], "user");
+    }
+
+    #[tokio::test]
+    async fn test_fill_in_middle_uses_raw_generate() {
+        let server = MockServer::start(
+            200,
+            r#"{"response": "\n    a + b\n<|endoftext|>", "done": true,
+                "prompt_eval_count": 12, "eval_count": 5}"#,
+        )
+        .await;
+        let backend = OllamaBackend {
+            base_url: server.url.clone(),
+            model: "qwen2.5-coder:1.5b-base".to_string(),
+            fim: FimTemplate::preset("qwen"),
+            temperature: None,
+            max_tokens: Some(64),
+        };
+        assert!(backend.supports_fim());
+
+        let resp = backend
+            .fill_in_middle(
+                "fn add(a: u32, b: u32) -> u32 {",
+                "}\n",
+                &GenerationOptions {
+                    stop: vec!["\n\n".to_string()],
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+
+        // The middle is kept verbatim, up to the stop token
+        assert_eq!(resp.body, "\n    a + b\n");
+        assert_eq!(resp.usage.unwrap().total_tokens, 17);
+
+        let request = &server.requests()[0];
+        assert_eq!(request.path, "/api/generate");
+        assert_eq!(request.body["raw"], true);
+        assert_eq!(request.body["options"]["num_predict"], 64);
+        let stop = &request.body["options"]["stop"];
+        assert_eq!(stop[0], "<|endoftext|>");
+        assert_eq!(stop[stop.as_array().unwrap().len() - 1], "\n\n");
+        assert_eq!(
+            request.body["prompt"],
+            "<|fim_prefix|>fn add(a: u32, b: u32) -> u32 {\
+             <|fim_suffix|>}\n<|fim_middle|>"
+        );
+    }
+}
@@ -0,0 +1,343 @@
+use crate::ai::backend::{
+    GenerationOptions, LLMBackend, LLMResponse, TokenUsage,
+};
+use crate::ai::helpers::{build_user_prompt, clean_output, system_prompt};
+use crate::ai::sse::{StreamOptions, read_chat_stream};
+use anyhow::Result;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Backend for any server exposing the OpenAI chat completions API, such as
+/// vLLM, LM Studio, Together or OpenRouter
+pub struct OpenAICompatBackend {
+    /// URL the `/chat/completions` path is appended to,
+    /// e.g. `http://localhost:8000/v1`
+    pub base_url: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    /// Header carrying the raw key; `Authorization: Bearer` when unset
+    pub auth_header: Option<String>,
+    /// Extra headers sent with every request
+    pub headers: BTreeMap<String, String>,
+    pub temperature: Option<f32>,
+    pub max_tokens: Option<u32>,
+}
+
+#[derive(Serialize)]
+struct Message<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<Message<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    stop: &'a [String],
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+    // Not every server reports usage
+    usage: Option<Usage>,
+}
+
+#[derive(Deserialize)]
+struct Usage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    total_tokens: u32,
+}
+
+#[derive(Deserialize)]
+struct Choice {
+    message: MessageResponse,
+}
+
+#[derive(Deserialize)]
+struct MessageResponse {
+    content: Option<String>,
+}
+
+#[async_trait]
+impl LLMBackend for OpenAICompatBackend {
+    async fn generate_function(
+        &self,
+        signature: &str,
+        doc_comment: Option<&str>,
+        instructions: Option<&str>,
+        context_snippets: Option<&[String]>,
+        language: &str,
+        options: &GenerationOptions,
+    ) -> Result<LLMResponse> {
+        let client = Client::new();
+
+        let system_prompt = system_prompt(language);
+
+        let user_prompt = build_user_prompt(
+            signature,
+            doc_comment,
+            instructions,
+            context_snippets,
+        );
+
+        let url =
+            format!("{}/chat/completions", self.base_url.trim_end_matches('/'));
+
+        let request_body = ChatRequest {
+            model: options.model(&self.model),
+            messages: vec![
+                Message { role: "system", content: &system_prompt },
+                Message { role: "user", content: &user_prompt },
+            ],
+            temperature: options.temperature.or(self.temperature),
+            max_tokens: options.max_tokens.or(self.max_tokens),
+            stop: &options.stop,
+            stream: options.on_delta.is_some(),
+            stream_options: options
+                .on_delta
+                .as_ref()
+                .map(|_| StreamOptions { include_usage: true }),
+        };
+
+        let mut request = client.post(&url).json(&request_body);
+        if let Some(api_key) = &self.api_key {
+            request = match &self.auth_header {
+                Some(header) => request.header(header, api_key),
+                None => request.bearer_auth(api_key),
+            };
+        }
+        for (name, value) in &self.headers {
+            request = request.header(name, value);
+        }
+
+        let resp = request.send().await?.error_for_status()?;
+
+        if let Some(on_delta) = &options.on_delta {
+            let (output, usage) = read_chat_stream(resp, on_delta).await?;
+            return Ok(LLMResponse { body: clean_output(&output), usage });
+        }
+
+        let resp = resp.json::<ChatResponse>().await?;
+
+        let output = resp
+            .choices
+            .first()
+            .and_then(|c| c.message.content.clone())
+            .unwrap_or_default();
+
+        Ok(LLMResponse {
+            body: clean_output(&output),
+            usage: resp.usage.map(|u| TokenUsage {
+                prompt_tokens: u.prompt_tokens,
+                completion_tokens: u.completion_tokens,
+                total_tokens: u.total_tokens,
+            }),
+        })
+    }
+
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock_server::MockServer;
+    use std::sync::{Arc, Mutex};
+
+    const RESPONSE: &str = r#"{
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "choices": [{
+            "index": 0,
+            "message": {"role": "assistant", "content": "```rust\na + b\n```"},
+            "finish_reason": "stop"
+        }],
+        "usage": {"prompt_tokens": 42, "completion_tokens": 3, "total_tokens": 45}
+    }"#;
+
+    fn backend(base_url: &str) -> OpenAICompatBackend {
+        OpenAICompatBackend {
+            base_url: format!("{base_url}/v1/"),
+            model: "qwen2.5-coder".to_string(),
+            api_key: None,
+            auth_header: None,
+            headers: BTreeMap::new(),
+            temperature: None,
+            max_tokens: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_request() {
+        let server = MockServer::start(200, RESPONSE).await;
+        let mut backend = backend(&server.url);
+        backend.api_key = Some("secret".to_string());
+        backend
+            .headers
+            .insert("HTTP-Referer".to_string(), "chace".to_string());
+
+        let resp = backend
+            .generate_function(
+                "fn add(a: u32, b: u32) -> u32",
+                Some("/// Adds"),
+                None,
+                None,
+                "rust",
+                &GenerationOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.body, "a + b");
+        let usage = resp.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, 42);
+        assert_eq!(usage.total_tokens, 45);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/v1/chat/completions");
+        assert_eq!(request.headers["authorization"], "Bearer secret");
+        assert_eq!(request.headers["http-referer"], "chace");
+        assert_eq!(request.body["model"], "qwen2.5-coder");
+        assert_eq!(request.body["messages"][0]["role"], "system");
+        assert!(
+            request.body["messages"][1]["content"]
+                .as_str()
+                .unwrap()
+                .ends_with("fn add(a: u32, b: u32) -> u32 {")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_request_overrides() {
+        let server = MockServer::start(200, RESPONSE).await;
+        let mut backend = backend(&server.url);
+        backend.temperature = Some(0.7);
+        backend.max_tokens = Some(512);
+
+        let options = GenerationOptions {
+            model: Some("qwen2.5-coder-32b".to_string()),
+            temperature: Some(0.1),
+            max_tokens: None,
+            stop: vec!["\n}".to_string()],
+            ..Default::default()
+        };
+        backend
+            .generate_function("fn f()", None, None, None, "rust", &options)
+            .await
+            .unwrap();
+
+        let request = &server.requests()[0];
+        assert_eq!(request.body["model"], "qwen2.5-coder-32b");
+        assert_eq!(request.body["temperature"], 0.1f32);
+        assert_eq!(request.body["max_tokens"], 512);
+        assert_eq!(request.body["stop"][0], "\n}");
+    }
+
+    #[tokio::test]
+    async fn test_streamed_completion() {
+        let server = MockServer::start(
+            200,
+            concat!(
+                "data: {\"choices\":[{\"delta\":{\"content\":\"```rust\\na\"}}]}\n\n",
+                "data: {\"choices\":[{\"delta\":{\"content\":\" + b\\n```\"}}]}\n\n",
+                "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":4,",
+                "\"completion_tokens\":3,\"total_tokens\":7}}\n\n",
+                "data: [DONE]\n\n",
+            ),
+        )
+        .await;
+
+        let deltas = Arc::new(Mutex::new(String::new()));
+        let sink = Arc::clone(&deltas);
+        let options = GenerationOptions {
+            on_delta: Some(Arc::new(move |delta| {
+                sink.lock().unwrap().push_str(delta)
+            })),
+            ..Default::default()
+        };
+
+        let resp = backend(&server.url)
+            .generate_function("fn f()", None, None, None, "rust", &options)
+            .await
+            .unwrap();
+
+        // Chunks are raw, the final body is cleaned
+        assert_eq!(*deltas.lock().unwrap(), "```rust\na + b\n```");
+        assert_eq!(resp.body, "a + b");
+        assert_eq!(resp.usage.unwrap().total_tokens, 7);
+
+        let request = &server.requests()[0];
+        assert_eq!(request.body["stream"], true);
+        assert_eq!(request.body["stream_options"]["include_usage"], true);
+    }
+
+    #[tokio::test]
+    async fn test_custom_auth_header_and_missing_usage() {
+        let server = MockServer::start(
+            200,
+            r#"{"choices": [{"message": {"content": "todo!()"}}]}"#,
+        )
+        .await;
+        let mut backend = backend(&server.url);
+        backend.api_key = Some("secret".to_string());
+        backend.auth_header = Some("api-key".to_string());
+
+        let resp = backend
+            .generate_function(
+                "fn f()",
+                None,
+                None,
+                None,
+                "rust",
+                &GenerationOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(resp.body, "todo!()");
+        assert!(resp.usage.is_none());
+
+        let request = &server.requests()[0];
+        assert_eq!(request.headers["api-key"], "secret");
+        assert!(!request.headers.contains_key("authorization"));
+    }
+
+    #[tokio::test]
+    async fn test_http_error_is_reported() {
+        let server =
+            MockServer::start(401, r#"{"error": {"message": "bad key"}}"#).await;
+
+        let result = backend(&server.url)
+            .generate_function(
+                "fn f()",
+                None,
+                None,
+                None,
+                "rust",
+                &GenerationOptions::default(),
+            )
+            .await;
+
+        assert!(result.unwrap_err().to_string().contains("401"));
+    }
+}
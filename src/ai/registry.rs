@@ -0,0 +1,198 @@
+use crate::ai::backend::LLMBackend;
+use crate::ai::config::BackendConfig;
+use crate::ai::gemini::{DEFAULT_GEMINI_URL, GeminiBackend};
+use crate::ai::groq_gpt_oss::GGPTOSSBackend;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Maps the `backend` of a request to the LLM backend serving it
+#[derive(Default)]
+pub struct BackendRegistry {
+    backends: HashMap<String, Arc<dyn LLMBackend>>,
+    /// Why a known backend could not be set up
+    unconfigured: HashMap<String, String>,
+    /// Backend of requests naming none
+    default: Option<String>,
+}
+
+impl BackendRegistry {
+    /// Registry with the built-in backends whose API keys are set
+    pub fn from_env() -> Self {
+        let mut registry = BackendRegistry::default();
+
+        match std::env::var("GEMINI_API_KEY") {
+            Ok(api_key) => registry.register(
+                "Gemini",
+                Arc::new(GeminiBackend {
+                    base_url: DEFAULT_GEMINI_URL.to_string(),
+                    api_key,
+                    model: "Gemini-2.5-flash".to_string(),
+                    temperature: None,
+                    max_tokens: None,
+                }),
+            ),
+            Err(_) => {
+                registry.mark_unconfigured("Gemini", "GEMINI_API_KEY is not set")
+            }
+        }
+
+        match std::env::var("GROQ_API_KEY") {
+            Ok(api_key) => registry.register(
+                "groq",
+                Arc::new(GGPTOSSBackend {
+                    api_key,
+                    model: "openai/gpt-oss-20b".to_string(),
+                    temperature: None,
+                    max_tokens: None,
+                }),
+            ),
+            Err(_) => {
+                registry.mark_unconfigured("groq", "GROQ_API_KEY is not set")
+            }
+        }
+
+        registry
+    }
+
+    /// Registers the backends of the config file. Those that cannot be set
+    /// up, e.g. for a missing key, are marked as not configured instead.
+    pub fn register_configured(
+        &mut self,
+        backends: &BTreeMap<String, BackendConfig>,
+    ) {
+        for (name, config) in backends {
+            match config.build() {
+                Ok(backend) => self.register(name, backend),
+                Err(e) => {
+                    eprintln!("backend {name} not configured: {e:#}");
+                    self.mark_unconfigured(name, &format!("{e:#}"));
+                }
+            }
+        }
+    }
+
+    /// Registers a backend, replacing any backend of the same name
+    pub fn register(&mut self, name: &str, backend: Arc<dyn LLMBackend>) {
+        self.unconfigured.remove(name);
+        self.backends.insert(name.to_string(), backend);
+    }
+
+    /// Records a backend that exists but cannot serve requests, unless a
+    /// working backend of that name was registered
+    pub fn mark_unconfigured(&mut self, name: &str, reason: &str) {
+        self.backends.remove(name);
+        self.unconfigured.insert(name.to_string(), reason.to_string());
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn LLMBackend>> {
+        self.backends.get(name).cloned()
+    }
+
+    pub fn set_default(&mut self, name: Option<String>) {
+        self.default = name;
+    }
+
+    pub fn default_name(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    /// Error for a known backend that was not set up
+    pub fn not_configured(&self, name: &str) -> Option<String> {
+        self.unconfigured
+            .get(name)
+            .map(|reason| format!("Backend not configured: {name} ({reason})"))
+    }
+
+    /// Names of the backends able to serve requests
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> =
+            self.backends.keys().map(String::as_str).collect();
+        names.sort();
+        names
+    }
+
+    /// Names of the backends streaming their output
+    pub fn streaming_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self
+            .backends
+            .iter()
+            .filter(|(_, backend)| backend.supports_streaming())
+            .map(|(name, _)| name.as_str())
+            .collect();
+        names.sort();
+        names
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::ollama::OllamaBackend;
+
+    fn ollama() -> Arc<dyn LLMBackend> {
+        Arc::new(OllamaBackend {
+            base_url: "http://localhost:11434".to_string(),
+            model: "llama3".to_string(),
+            fim: None,
+            temperature: None,
+            max_tokens: None,
+        })
+    }
+
+    #[test]
+    fn test_unconfigured_backends_explain_why() {
+        let mut registry = BackendRegistry::default();
+        registry.mark_unconfigured("Gemini", "GEMINI_API_KEY is not set");
+        registry.register("local", ollama());
+
+        assert!(registry.get("Gemini").is_none());
+        assert_eq!(
+            registry.not_configured("Gemini").unwrap(),
+            "Backend not configured: Gemini (GEMINI_API_KEY is not set)"
+        );
+        assert!(registry.get("local").is_some());
+        assert!(registry.not_configured("other").is_none());
+        assert_eq!(registry.names(), vec!["local"]);
+
+        // Configuring the backend later clears the error
+        registry.register("Gemini", ollama());
+        assert!(registry.not_configured("Gemini").is_none());
+        assert_eq!(registry.names(), vec!["Gemini", "local"]);
+    }
+
+    #[test]
+    fn test_register_configured_backends() {
+        let backends: BTreeMap<String, BackendConfig> = toml::from_str(
+            r#"
+[local]
+provider = "ollama"
+model = "qwen2.5-coder:7b"
+
+[vllm]
+provider = "openai_compat"
+model = "qwen"
+base_url = "http://localhost:8000/v1"
+
+[claude]
+provider = "anthropic"
+model = "claude-sonnet-4-5"
+api_key_env = "CHACE_TEST_UNSET_KEY"
+"#,
+        )
+        .unwrap();
+
+        let mut registry = BackendRegistry::default();
+        registry.register_configured(&backends);
+
+        assert!(registry.get("local").is_some());
+        assert!(registry.get("claude").is_none());
+        assert_eq!(
+            registry.not_configured("claude").unwrap(),
+            "Backend not configured: claude (CHACE_TEST_UNSET_KEY is not set)"
+        );
+        assert_eq!(registry.names(), vec!["local", "vllm"]);
+        assert_eq!(registry.streaming_names(), vec!["vllm"]);
+    }
+}
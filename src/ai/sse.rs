@@ -0,0 +1,138 @@
+use crate::ai::backend::{DeltaSink, TokenUsage};
+use anyhow::Result;
+use reqwest::Response;
+use serde::{Deserialize, Serialize};
+
+/// Asks OpenAI style servers for usage in the last chunk of a stream
+#[derive(Serialize)]
+pub struct StreamOptions {
+    pub include_usage: bool,
+}
+
+/// A chunk of an OpenAI style streamed chat completion
+#[derive(Deserialize)]
+struct ChatChunk {
+    #[serde(default)]
+    choices: Vec<ChunkChoice>,
+    usage: Option<ChunkUsage>,
+    /// Groq reports usage here instead
+    x_groq: Option<GroqExtension>,
+}
+
+#[derive(Deserialize)]
+struct ChunkChoice {
+    delta: Delta,
+}
+
+#[derive(Deserialize)]
+struct Delta {
+    content: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ChunkUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+    total_tokens: u32,
+}
+
+#[derive(Deserialize)]
+struct GroqExtension {
+    usage: Option<ChunkUsage>,
+}
+
+/// Calls `on_event` with the data of each event of a server-sent events
+/// response, until the stream ends or sends `[DONE]`
+pub async fn read_events(
+    mut resp: Response,
+    mut on_event: impl FnMut(&str) -> Result<()>,
+) -> Result<()> {
+    let mut buffer = Vec::new();
+
+    while let Some(bytes) = resp.chunk().await? {
+        buffer.extend_from_slice(&bytes);
+
+        // Events may be split anywhere, only whole lines are parsed
+        while let Some(end) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let Some(data) = line.trim_end().strip_prefix("data:") else {
+                continue;
+            };
+            let data = data.trim_start();
+            if data == "[DONE]" {
+                return Ok(());
+            }
+            on_event(data)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Reads an OpenAI style chat completion stream, passing each text delta to
+/// `on_delta`. Returns the whole text and the usage, if reported.
+pub async fn read_chat_stream(
+    resp: Response,
+    on_delta: &DeltaSink,
+) -> Result<(String, Option<TokenUsage>)> {
+    let mut output = String::new();
+    let mut usage = None;
+
+    read_events(resp, |data| {
+        let chunk: ChatChunk = serde_json::from_str(data)?;
+        for choice in chunk.choices {
+            if let Some(text) = choice.delta.content
+                && !text.is_empty()
+            {
+                on_delta(&text);
+                output.push_str(&text);
+            }
+        }
+        if let Some(u) = chunk.usage.or(chunk.x_groq.and_then(|x| x.usage)) {
+            usage = Some(TokenUsage {
+                prompt_tokens: u.prompt_tokens,
+                completion_tokens: u.completion_tokens,
+                total_tokens: u.total_tokens,
+            });
+        }
+        Ok(())
+    })
+    .await?;
+
+    Ok((output, usage))
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock_server::MockServer;
+    use std::sync::{Arc, Mutex};
+
+    #[tokio::test]
+    async fn test_chat_stream_deltas_and_usage() {
+        let body = concat!(
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\"a +\"}}]}\n\n",
+            ": keep-alive\n\n",
+            "data: {\"choices\":[{\"delta\":{\"content\":\" b\"}}]}\n\n",
+            "data: {\"choices\":[],\"x_groq\":{\"usage\":{\"prompt_tokens\":9,",
+            "\"completion_tokens\":2,\"total_tokens\":11}}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let server = MockServer::start(200, body).await;
+        let resp = reqwest::get(&server.url).await.unwrap();
+
+        let deltas = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&deltas);
+        let on_delta: DeltaSink =
+            Arc::new(move |text| sink.lock().unwrap().push(text.to_string()));
+
+        let (output, usage) = read_chat_stream(resp, &on_delta).await.unwrap();
+        assert_eq!(output, "a + b");
+        assert_eq!(*deltas.lock().unwrap(), vec!["a +", " b"]);
+        assert_eq!(usage.unwrap().total_tokens, 11);
+    }
+}
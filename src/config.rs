@@ -0,0 +1,218 @@
+use crate::ai::config::BackendConfig;
+use crate::languages::config::LanguageConfig;
+use anyhow::{Context, Result, bail};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Backends registered from the environment when their key is set
+const BUILTIN_BACKENDS: [&str; 2] = ["Gemini", "groq"];
+
+/// Requests generated at once for a connection, unless configured
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// The chace config file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// Socket to listen on, unless `SOCKET_PATH` is set
+    pub socket_path: Option<String>,
+    /// Backend of requests naming none
+    pub default_backend: Option<String>,
+    /// Requests generated at once for a connection, the others wait
+    pub max_concurrent_requests: Option<usize>,
+    #[serde(default)]
+    pub backends: BTreeMap<String, BackendConfig>,
+    #[serde(default)]
+    pub languages: BTreeMap<String, LanguageConfig>,
+}
+
+impl Config {
+    /// Location of the config file: `CHACE_CONFIG`, or `config.toml` in the
+    /// chace config directory
+    pub fn default_path() -> Option<PathBuf> {
+        match std::env::var_os("CHACE_CONFIG") {
+            Some(path) => Some(PathBuf::from(path)),
+            None => dirs::config_dir().map(|d| d.join("chace/config.toml")),
+        }
+    }
+
+    /// Reads and validates the config file, resolving relative paths against
+    /// the directory of the file. A missing file is an empty config.
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Config::default());
+            }
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("reading {}", path.display()));
+            }
+        };
+
+        let mut config: Config = toml::from_str(&text)
+            .with_context(|| format!("parsing {}", path.display()))?;
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        for backend in config.backends.values_mut() {
+            if let Some(grammar_file) = &mut backend.grammar_file {
+                *grammar_file = base.join(&grammar_file);
+            }
+            if let Some(key_file) = &mut backend.api_key_file {
+                *key_file = base.join(&key_file);
+            }
+        }
+        for language in config.languages.values_mut() {
+            if let Some(grammar) = &mut language.grammar {
+                grammar.library = base.join(&grammar.library);
+                grammar.query = base.join(&grammar.query);
+            }
+        }
+
+        config
+            .validate()
+            .with_context(|| format!("invalid config {}", path.display()))?;
+        Ok(config)
+    }
+
+    /// Checks the backend options and that every backend referenced exists
+    pub fn validate(&self) -> Result<()> {
+        for (name, backend) in &self.backends {
+            backend.validate(name)?;
+        }
+
+        if self.max_concurrent_requests == Some(0) {
+            bail!("max_concurrent_requests: must be greater than 0");
+        }
+
+        if let Some(name) = &self.default_backend {
+            self.check_backend("default_backend", name)?;
+        }
+        for (language, config) in &self.languages {
+            if let Some(name) = &config.backend {
+                let key = format!("languages.{language}.backend");
+                self.check_backend(&key, name)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn check_backend(&self, key: &str, name: &str) -> Result<()> {
+        if !self.backends.contains_key(name)
+            && !BUILTIN_BACKENDS.contains(&name)
+        {
+            bail!("{key}: no backend named `{name}`");
+        }
+        Ok(())
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Config {
+        toml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn test_load_config_file() {
+        let dir = std::env::temp_dir()
+            .join(format!("chace_config_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.toml");
+        std::fs::write(
+            &path,
+            r##"
+socket_path = "/run/user/1000/chace.sock"
+default_backend = "local"
+
+[backends.local]
+provider = "llama_cpp"
+grammar_file = "body.gbnf"
+
+[languages.python]
+backend = "groq"
+
+[languages.hcl]
+file_types = ["terraform"]
+grammar = { library = "lib/hcl.so", query = "/etc/chace/hcl.scm", doc_prefixes = ["#"] }
+"##,
+        )
+        .unwrap();
+
+        let config = Config::load(&path).unwrap();
+        assert_eq!(
+            config.socket_path.as_deref(),
+            Some("/run/user/1000/chace.sock")
+        );
+        assert_eq!(config.default_backend.as_deref(), Some("local"));
+        assert_eq!(
+            config.backends["local"].grammar_file,
+            Some(dir.join("body.gbnf"))
+        );
+        let hcl = config.languages["hcl"].grammar.as_ref().unwrap();
+        assert_eq!(hcl.library, dir.join("lib/hcl.so"));
+        assert_eq!(hcl.query, PathBuf::from("/etc/chace/hcl.scm"));
+
+        let missing = Config::load(&dir.join("missing.toml")).unwrap();
+        assert!(missing.backends.is_empty());
+        assert!(missing.default_backend.is_none());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_unknown_backend_references_are_rejected() {
+        let config = parse("default_backend = \"nope\"\n");
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "default_backend: no backend named `nope`"
+        );
+
+        let config = parse("[languages.rust]\nbackend = \"local\"\n");
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "languages.rust.backend: no backend named `local`"
+        );
+
+        // Built-in backends need no table
+        assert!(parse("default_backend = \"groq\"\n").validate().is_ok());
+    }
+
+    #[test]
+    fn test_concurrency_limit_must_be_positive() {
+        let config = parse("max_concurrent_requests = 0\n");
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "max_concurrent_requests: must be greater than 0"
+        );
+        assert!(parse("max_concurrent_requests = 8\n").validate().is_ok());
+    }
+
+    #[test]
+    fn test_unknown_keys_are_named() {
+        let err = toml::from_str::<Config>("socket = \"/tmp/x.sock\"\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown field `socket`"));
+
+        let err = toml::from_str::<Config>("[languages.rust]\nfiletypes = []\n")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown field `filetypes`"));
+    }
+
+    #[test]
+    fn test_backend_errors_are_reported() {
+        let config =
+            parse("[backends.local]\nprovider = \"llama_cpp\"\nmax_tokens = 0\n");
+        assert_eq!(
+            config.validate().unwrap_err().to_string(),
+            "backends.local.max_tokens: must be greater than 0"
+        );
+    }
+}
@@ -0,0 +1,276 @@
+use crate::languages::helpers::{
+    Placeholder, body_comments_end, collect_nodes, extract_body_instructions,
+    extract_doc_comment, extract_signature, find_innermost_at, is_comment,
+    is_empty_body, is_placeholder_body, text_for,
+};
+use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
+use tree_sitter::{Node, Parser};
+use tree_sitter_c;
+
+const PLACEHOLDERS: &[Placeholder] = &[
+    Placeholder::Call { name: "abort", message: None },
+    Placeholder::Call { name: "assert", message: Some("not implemented") },
+    Placeholder::Exact("assert(0)"),
+];
+
+pub struct CBackend;
+
+impl LanguageStandard for CBackend {
+    fn find_empty_function_at_cursor(
+        &self,
+        source_code: &str,
+        cursor_byte: usize,
+    ) -> Option<FunctionInfo> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_c::LANGUAGE.into())
+            .expect("Failed to load C grammar");
+
+        let tree = parser.parse(source_code, None)?;
+        let root = tree.root_node();
+        let node =
+            find_innermost_at(root, cursor_byte, &["function_definition"])?;
+
+        function_info(node, root, source_code, PLACEHOLDERS)
+    }
+
+    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
+        let mut parser = Parser::new();
+
+        parser
+            .set_language(&tree_sitter_c::LANGUAGE.into())
+            .expect("Error loading C grammar");
+
+        let tree = parser.parse(source_code, None).unwrap();
+        let root = tree.root_node();
+
+        collect_nodes(root, &["function_definition"])
+            .into_iter()
+            .filter_map(|node| function_info(node, root, source_code, PLACEHOLDERS))
+            .collect()
+    }
+}
+
+//---------------------- C family helpers -------------------------------------
+// Shared with the C++ backend, whose grammar uses the same node kinds
+
+/// Builds the function info for an empty `function_definition`. When the
+/// definition has no doc comment of its own, the Doxygen comment of a
+/// matching declaration elsewhere in the source is used instead.
+pub(crate) fn function_info(
+    node: Node,
+    root: Node,
+    source: &str,
+    placeholders: &[Placeholder],
+) -> Option<FunctionInfo> {
+    let body_node = node.child_by_field_name("body")?;
+    if !is_empty_body(text_for(source, &body_node))
+        && !is_placeholder_body(&body_node, source, placeholders)
+    {
+        return None;
+    }
+
+    let outer = with_template(node);
+    let doc_comment = doxygen_comment(&outer, source).or_else(|| {
+        // overloads share the name, so the parameter types must match too
+        let name = qualified_name(node, source)?;
+        let parameters = parameter_types(node, source)?;
+        collect_nodes(root, &["declaration", "field_declaration"])
+            .into_iter()
+            .filter(|decl| {
+                qualified_name(*decl, source).as_ref() == Some(&name)
+                    && parameter_types(*decl, source).as_ref()
+                        == Some(&parameters)
+            })
+            .find_map(|decl| doxygen_comment(&with_template(decl), source))
+    });
+
+    Some(FunctionInfo {
+        signature: extract_signature(&outer, source),
+        doc_comment,
+        instructions: extract_body_instructions(&body_node, source),
+        instructions_end: body_comments_end(&body_node),
+        start_byte: body_node.start_byte() + 1,
+        end_byte: body_node.end_byte() - 1,
+        indent: None,
+    })
+}
+
+/// Extracts a `/** */` or `///` comment preceding the node
+fn doxygen_comment(node: &Node, source: &str) -> Option<String> {
+    extract_doc_comment(node, source, "/**")
+        .or_else(|| extract_doc_comment(node, source, "///"))
+}
+
+/// Returns the wrapping `template_declaration`, if any
+fn with_template(node: Node) -> Node {
+    match node.parent() {
+        Some(p) if p.kind() == "template_declaration" => p,
+        _ => node,
+    }
+}
+
+/// Fully qualified name of the function a definition or declaration
+/// declares, e.g. `geo::Foo::bar` for `int Foo::bar()` inside
+/// `namespace geo`, or for `int bar();` inside `class Foo` in that namespace
+fn qualified_name(node: Node, source: &str) -> Option<String> {
+    let name = function_declarator(node)?.child_by_field_name("declarator")?;
+    let mut parts = vec![text_for(source, &name).replace(char::is_whitespace, "")];
+
+    let mut current = node.parent();
+    while let Some(scope) = current {
+        if matches!(
+            scope.kind(),
+            "namespace_definition" | "class_specifier" | "struct_specifier"
+        ) && let Some(scope_name) = scope.child_by_field_name("name")
+        {
+            parts.push(text_for(source, &scope_name).to_string());
+        }
+        current = scope.parent();
+    }
+
+    parts.reverse();
+    Some(parts.join("::"))
+}
+
+/// Types of the parameters a definition or declaration takes, without names,
+/// default values or whitespace, e.g. `["constchar*", "int"]` for
+/// `(const char *s, int n = 0)`
+fn parameter_types(node: Node, source: &str) -> Option<Vec<String>> {
+    let parameters =
+        function_declarator(node)?.child_by_field_name("parameters")?;
+
+    let mut cursor = parameters.walk();
+    let types: Vec<String> = parameters
+        .named_children(&mut cursor)
+        .filter(|p| !is_comment(p))
+        .map(|parameter| {
+            let end = parameter
+                .child_by_field_name("default_value")
+                .map_or(parameter.end_byte(), |d| d.start_byte());
+            let text = match parameter_name(parameter) {
+                Some(name) => format!(
+                    "{}{}",
+                    &source[parameter.start_byte()..name.start_byte()],
+                    &source[name.end_byte()..end],
+                ),
+                None => source[parameter.start_byte()..end].to_string(),
+            };
+            text.trim_end()
+                .trim_end_matches('=')
+                .replace(char::is_whitespace, "")
+        })
+        .collect();
+
+    // `f(void)` declares the same function as `f()`
+    if types == ["void"] {
+        return Some(Vec::new());
+    }
+    Some(types)
+}
+
+/// The identifier a parameter declares, if it is named
+fn parameter_name(parameter: Node) -> Option<Node> {
+    let mut declarator = parameter.child_by_field_name("declarator")?;
+    while declarator.kind() != "identifier" {
+        declarator = declarator
+            .child_by_field_name("declarator")
+            .or_else(|| declarator.named_child(0))?;
+    }
+    Some(declarator)
+}
+
+/// The `function_declarator` of a definition or declaration
+fn function_declarator(node: Node) -> Option<Node> {
+    let mut declarator = node.child_by_field_name("declarator")?;
+    while declarator.kind() != "function_declarator" {
+        // the `&` of `int& f()` wraps it in a declarator without fields
+        declarator = declarator
+            .child_by_field_name("declarator")
+            .or_else(|| declarator.named_child(0))?;
+    }
+    Some(declarator)
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_simple_empty_function() {
+        let backend = CBackend;
+        let code = r#"
+/**
+ * Adds two numbers
+ */
+int add(int a, int b) {
+}"#;
+        let cursor_byte = code.find('}').unwrap() - 1;
+
+        let result = backend.find_empty_function_at_cursor(code, cursor_byte);
+
+        assert!(result.is_some(), "Should find the empty function");
+        let info = result.unwrap();
+
+        assert_eq!(info.signature, "int add(int a, int b)");
+        assert_eq!(info.doc_comment, Some("Adds two numbers".to_string()));
+
+        let body_content = &code[info.start_byte..info.end_byte];
+        assert!(body_content.trim().is_empty());
+    }
+
+    #[test]
+    fn test_doc_comment_from_prototype() {
+        let backend = CBackend;
+        let code = r#"
+/// Returns the larger value
+int max(int a, int b);
+
+int other(void);
+
+int max(int a, int b) {
+}"#;
+        let cursor_byte = code.rfind('{').unwrap() + 1;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.doc_comment, Some("Returns the larger value".to_string()));
+    }
+
+    #[test]
+    fn test_c_stubs_count_as_empty() {
+        let backend = CBackend;
+        let code = r#"
+int a(void) { abort(); }
+int b(void) { assert(0); }
+int c(void) { assert(0 && "not implemented"); }
+int d(int x) { assert(x > 0); }"#;
+
+        let funcs = backend.find_empty_functions(code);
+        let signatures: Vec<_> =
+            funcs.iter().map(|f| f.signature.as_str()).collect();
+        assert_eq!(signatures, ["int a(void)", "int b(void)", "int c(void)"]);
+    }
+
+    #[test]
+    fn test_ignores_populated_function() {
+        let backend = CBackend;
+        let code = "int has_code(void) { return puts(\"hi\"); }";
+        let cursor_byte = code.find("puts").unwrap();
+
+        let result = backend.find_empty_function_at_cursor(code, cursor_byte);
+        assert!(result.is_none(), "Should ignore functions with bodies");
+    }
+
+    #[test]
+    fn test_find_pointer_returning_function() {
+        let backend = CBackend;
+        let code = "static char *dup(const char *s) {}\nint used(void) { return 0; }";
+
+        let funcs = backend.find_empty_functions(code);
+        assert_eq!(funcs.len(), 1);
+        assert_eq!(funcs[0].signature, "static char *dup(const char *s)");
+    }
+}
@@ -0,0 +1,16 @@
+use crate::languages::dynamic::GrammarConfig;
+use serde::Deserialize;
+
+/// A `[languages.<name>]` table of the config file, either tuning a
+/// built-in language or adding one from a grammar library
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LanguageConfig {
+    /// Additional `file_type` values served by the language
+    #[serde(default)]
+    pub file_types: Vec<String>,
+    /// Backend of requests for this language naming none
+    pub backend: Option<String>,
+    /// Loads the language at runtime instead of using a built-in one
+    pub grammar: Option<GrammarConfig>,
+}
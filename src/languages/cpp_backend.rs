@@ -0,0 +1,198 @@
+use crate::languages::c_backend::function_info;
+use crate::languages::helpers::{Placeholder, collect_nodes, find_innermost_at};
+use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
+use tree_sitter::Parser;
+use tree_sitter_cpp;
+
+const PLACEHOLDERS: &[Placeholder] = &[
+    Placeholder::Throw { class: "logic_error", message: Some("not implemented") },
+    Placeholder::Throw { class: "runtime_error", message: Some("not implemented") },
+    Placeholder::Call { name: "abort", message: None },
+    Placeholder::Call { name: "assert", message: Some("not implemented") },
+    Placeholder::Exact("assert(false)"),
+    Placeholder::Exact("assert(0)"),
+];
+
+pub struct CppBackend;
+
+impl LanguageStandard for CppBackend {
+    fn find_empty_function_at_cursor(
+        &self,
+        source_code: &str,
+        cursor_byte: usize,
+    ) -> Option<FunctionInfo> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_cpp::LANGUAGE.into())
+            .expect("Failed to load C++ grammar");
+
+        let tree = parser.parse(source_code, None)?;
+        let root = tree.root_node();
+        let node =
+            find_innermost_at(root, cursor_byte, &["function_definition"])?;
+
+        function_info(node, root, source_code, PLACEHOLDERS)
+    }
+
+    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
+        let mut parser = Parser::new();
+
+        parser
+            .set_language(&tree_sitter_cpp::LANGUAGE.into())
+            .expect("Error loading C++ grammar");
+
+        let tree = parser.parse(source_code, None).unwrap();
+        let root = tree.root_node();
+
+        collect_nodes(root, &["function_definition"])
+            .into_iter()
+            .filter_map(|node| function_info(node, root, source_code, PLACEHOLDERS))
+            .collect()
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_out_of_class_member_definition() {
+        let backend = CppBackend;
+        let code = r#"
+namespace geo {
+class Rect {
+  public:
+    /**
+     * Computes the area
+     */
+    double area() const;
+
+  private:
+    double w, h;
+};
+}
+
+double geo::Rect::area() const {
+}"#;
+        let cursor_byte = code.rfind('}').unwrap() - 1;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.signature, "double geo::Rect::area() const");
+        assert_eq!(info.doc_comment, Some("Computes the area".to_string()));
+
+        let body_content = &code[info.start_byte..info.end_byte];
+        assert!(body_content.trim().is_empty());
+    }
+
+    #[test]
+    fn test_find_template_function() {
+        let backend = CppBackend;
+        let code = r#"
+/// Returns the larger value
+template <typename T>
+T max(T a, T b) {
+}"#;
+        let cursor_byte = code.find('{').unwrap() + 1;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.signature, "template <typename T>\nT max(T a, T b)");
+        assert_eq!(info.doc_comment, Some("Returns the larger value".to_string()));
+    }
+
+    #[test]
+    fn test_find_inline_method() {
+        let backend = CppBackend;
+        let code = "struct Counter {\n  int next() {}\n  int value;\n};";
+        let cursor_byte = code.find("{}").unwrap() + 1;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.signature, "int next()");
+    }
+
+    #[test]
+    fn test_ignores_populated_function() {
+        let backend = CppBackend;
+        let code = "int Foo::bar() { return 1; }";
+        let cursor_byte = code.find("return").unwrap();
+
+        let result = backend.find_empty_function_at_cursor(code, cursor_byte);
+        assert!(result.is_none(), "Should ignore functions with bodies");
+    }
+
+    #[test]
+    fn test_throw_placeholder_counts_as_empty() {
+        let backend = CppBackend;
+        let code = "int a() { throw std::logic_error(\"not implemented\"); }\nint b() { throw std::logic_error(\"bad\"); }";
+
+        let funcs = backend.find_empty_functions(code);
+        assert_eq!(funcs.len(), 1);
+        assert_eq!(funcs[0].signature, "int a()");
+    }
+
+    #[test]
+    fn test_overloads_get_their_own_declaration_doc() {
+        let backend = CppBackend;
+        let code = r#"
+struct Buffer {
+    /// Appends one byte
+    void append(char c);
+    /// Appends a string
+    void append(const char* s, int n = -1);
+};
+
+void Buffer::append(const char *text, int len) {}
+void Buffer::append(char byte) {}"#;
+
+        let docs: Vec<_> = backend
+            .find_empty_functions(code)
+            .into_iter()
+            .map(|f| f.doc_comment)
+            .collect();
+        assert_eq!(docs, [
+            Some("Appends a string".to_string()),
+            Some("Appends one byte".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn test_reference_pointer_and_operator_definitions() {
+        let backend = CppBackend;
+        let code = r#"
+class Vec {
+  public:
+    /// Element at i
+    int& at(int i);
+    /// Raw storage
+    const int* data() const;
+    /// Same elements
+    bool operator==(const Vec& other) const;
+    /// Adds n to every element
+    Vec& operator+=(int n);
+};
+
+int& Vec::at(int i) {}
+const int* Vec::data() const {}
+bool Vec::operator==(const Vec& other) const {}
+Vec& Vec::operator+=(int n) {}"#;
+
+        let funcs = backend.find_empty_functions(code);
+        let found: Vec<_> = funcs
+            .iter()
+            .map(|f| (f.signature.as_str(), f.doc_comment.as_deref()))
+            .collect();
+        assert_eq!(found, [
+            ("int& Vec::at(int i)", Some("Element at i")),
+            ("const int* Vec::data() const", Some("Raw storage")),
+            (
+                "bool Vec::operator==(const Vec& other) const",
+                Some("Same elements")
+            ),
+            ("Vec& Vec::operator+=(int n)", Some("Adds n to every element")),
+        ]);
+    }
+}
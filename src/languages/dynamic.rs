@@ -0,0 +1,119 @@
+use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
+use crate::languages::query_backend::{QueryBackend, QueryConfig};
+use anyhow::{Context, Result, bail};
+use libloading::{Library, Symbol};
+use serde::Deserialize;
+use std::path::PathBuf;
+use tree_sitter::ffi::TSLanguage;
+use tree_sitter::{LANGUAGE_VERSION, Language, MIN_COMPATIBLE_LANGUAGE_VERSION};
+
+/// A grammar shared library, as built by `tree-sitter build`, and the query
+/// describing its functions
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GrammarConfig {
+    pub library: PathBuf,
+    pub query: PathBuf,
+    /// Exported language function, `tree_sitter_<name>` by default
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub doc_prefixes: Vec<String>,
+    #[serde(default)]
+    pub field_kinds: Vec<String>,
+    pub field_terminator: Option<String>,
+}
+
+/// A query backend over a grammar loaded at runtime. The library stays
+/// loaded for as long as the backend is alive.
+pub struct DynamicBackend {
+    // Dropped before the library the language points into
+    backend: QueryBackend,
+    _library: Library,
+}
+
+impl DynamicBackend {
+    /// Loads the grammar library and compiles its query
+    pub fn load(name: &str, config: &GrammarConfig) -> Result<Self> {
+        let query_source = std::fs::read_to_string(&config.query)
+            .with_context(|| format!("reading {}", config.query.display()))?;
+
+        // SAFETY: the library is trusted configuration, like the binary
+        // itself; its initializers run on load
+        let library = unsafe { Library::new(&config.library) }
+            .with_context(|| format!("loading {}", config.library.display()))?;
+
+        let symbol = config
+            .symbol
+            .clone()
+            .unwrap_or_else(|| format!("tree_sitter_{}", name.replace('-', "_")));
+        let language = unsafe {
+            let language_fn: Symbol<unsafe extern "C" fn() -> *const TSLanguage> =
+                library
+                    .get(symbol.as_bytes())
+                    .with_context(|| format!("looking up {symbol}"))?;
+            let raw = language_fn();
+            if raw.is_null() {
+                bail!("{symbol} returned no language");
+            }
+            Language::from_raw(raw)
+        };
+
+        let version = language.abi_version();
+        if !(MIN_COMPATIBLE_LANGUAGE_VERSION..=LANGUAGE_VERSION).contains(&version)
+        {
+            bail!(
+                "grammar ABI version {version} is not supported (expected \
+                 {MIN_COMPATIBLE_LANGUAGE_VERSION} to {LANGUAGE_VERSION})"
+            );
+        }
+
+        let backend = QueryBackend::new(
+            language,
+            &query_source,
+            QueryConfig {
+                doc_prefixes: config.doc_prefixes.clone(),
+                field_kinds: config.field_kinds.clone(),
+                field_terminator: config.field_terminator.clone(),
+            },
+        )
+        .with_context(|| format!("compiling {}", config.query.display()))?;
+
+        Ok(DynamicBackend { backend, _library: library })
+    }
+}
+
+impl LanguageStandard for DynamicBackend {
+    fn find_empty_function_at_cursor(
+        &self,
+        source_code: &str,
+        cursor_byte: usize,
+    ) -> Option<FunctionInfo> {
+        self.backend.find_empty_function_at_cursor(source_code, cursor_byte)
+    }
+
+    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
+        self.backend.find_empty_functions(source_code)
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_failure_is_an_error() {
+        let config = GrammarConfig {
+            library: PathBuf::from("/nonexistent/elixir.so"),
+            query: PathBuf::from("queries/rust.scm"),
+            symbol: None,
+            doc_prefixes: Vec::new(),
+            field_kinds: Vec::new(),
+            field_terminator: None,
+        };
+
+        let err = DynamicBackend::load("elixir", &config).err().unwrap();
+        assert!(format!("{err:#}").contains("/nonexistent/elixir.so"));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::languages::helpers::{
+    Placeholder, body_comments_end, collect_nodes, extract_body_instructions,
+    extract_doc_comment, extract_signature, find_innermost_at, is_empty_body,
+    is_placeholder_body, text_for,
+};
+use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
+use tree_sitter::{Node, Parser};
+use tree_sitter_go;
+
+/// Function and method declarations, the latter carrying their receiver
+const FUNCTION_KINDS: &[&str] = &["function_declaration", "method_declaration"];
+
+const PLACEHOLDERS: &[Placeholder] = &[
+    Placeholder::Call { name: "panic", message: Some("not implemented") },
+    Placeholder::Call { name: "panic", message: Some("TODO") },
+];
+
+pub struct GoBackend;
+
+impl LanguageStandard for GoBackend {
+    fn find_empty_function_at_cursor(
+        &self,
+        source_code: &str,
+        cursor_byte: usize,
+    ) -> Option<FunctionInfo> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_go::LANGUAGE.into())
+            .expect("Failed to load Go grammar");
+
+        let tree = parser.parse(source_code, None)?;
+        let node =
+            find_innermost_at(tree.root_node(), cursor_byte, FUNCTION_KINDS)?;
+
+        function_info(node, source_code)
+    }
+
+    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
+        let mut parser = Parser::new();
+
+        parser
+            .set_language(&tree_sitter_go::LANGUAGE.into())
+            .expect("Error loading Go grammar");
+
+        let tree = parser.parse(source_code, None).unwrap();
+
+        collect_nodes(tree.root_node(), FUNCTION_KINDS)
+            .into_iter()
+            .filter_map(|node| function_info(node, source_code))
+            .collect()
+    }
+}
+
+//---------------------- Backend specific helpers -----------------------------
+
+fn function_info(node: Node, source: &str) -> Option<FunctionInfo> {
+    let body_node = node.child_by_field_name("body")?;
+    if !is_empty_body(text_for(source, &body_node))
+        && !is_placeholder_body(&body_node, source, PLACEHOLDERS)
+    {
+        return None;
+    }
+
+    // The signature runs from `func` to the body, so a method's receiver
+    // is part of it
+    Some(FunctionInfo {
+        signature: extract_signature(&node, source),
+        doc_comment: extract_doc_comment(&node, source, "//"),
+        instructions: extract_body_instructions(&body_node, source),
+        instructions_end: body_comments_end(&body_node),
+        start_byte: body_node.start_byte() + 1,
+        end_byte: body_node.end_byte() - 1,
+        indent: None,
+    })
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_simple_empty_function() {
+        let backend = GoBackend;
+        let code = r#"
+package main
+
+// Greet returns a greeting
+func Greet(name string) string {
+}"#;
+        let cursor_byte = code.find('}').unwrap() - 1;
+
+        let result = backend.find_empty_function_at_cursor(code, cursor_byte);
+
+        assert!(result.is_some(), "Should find the empty function");
+        let info = result.unwrap();
+
+        assert_eq!(info.signature, "func Greet(name string) string");
+        assert_eq!(info.doc_comment, Some("Greet returns a greeting".to_string()));
+
+        let body_content = &code[info.start_byte..info.end_byte];
+        assert!(body_content.trim().is_empty());
+    }
+
+    #[test]
+    fn test_find_method_with_receiver() {
+        let backend = GoBackend;
+        let code = r#"
+package shapes
+
+// Area computes the area
+// of the rectangle
+func (r *Rect) Area() float64 {
+}"#;
+        let cursor_byte = code.find('{').unwrap() + 1;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.signature, "func (r *Rect) Area() float64");
+        assert_eq!(
+            info.doc_comment,
+            Some("Area computes the area\nof the rectangle".to_string())
+        );
+    }
+
+    #[test]
+    fn test_ignores_populated_function() {
+        let backend = GoBackend;
+        let code = "package main\n\nfunc hasCode() { fmt.Println(\"hi\") }";
+        let cursor_byte = code.find("fmt").unwrap();
+
+        let result = backend.find_empty_function_at_cursor(code, cursor_byte);
+        assert!(result.is_none(), "Should ignore functions with bodies");
+    }
+
+    #[test]
+    fn test_cursor_outside_function() {
+        let backend = GoBackend;
+        let code = "package main\n\nfunc empty() {}\n\n// cursor is here";
+        let cursor_byte = code.len() - 1;
+
+        let result = backend.find_empty_function_at_cursor(code, cursor_byte);
+        assert!(result.is_none(), "Should ignore if cursor is outside bounds");
+    }
+
+    #[test]
+    fn test_find_empty_functions() {
+        let backend = GoBackend;
+        let code = "package main\n\nfunc A() {}\n\nfunc (s S) B() int { return 1 }\n\nfunc (s *S) C() {\n}\n";
+
+        let funcs = backend.find_empty_functions(code);
+        let names: Vec<_> =
+            funcs.iter().map(|f| f.signature.as_str()).collect();
+        assert_eq!(names, vec!["func A()", "func (s *S) C()"]);
+    }
+
+    #[test]
+    fn test_panic_placeholder_counts_as_empty() {
+        let backend = GoBackend;
+        let code = "package main\n\nfunc A() int {\n\tpanic(\"not implemented\")\n}\n\nfunc B() int {\n\tpanic(err)\n}\n";
+
+        let funcs = backend.find_empty_functions(code);
+        assert_eq!(funcs.len(), 1);
+        assert_eq!(funcs[0].signature, "func A() int");
+    }
+
+    #[test]
+    fn test_comment_only_body_becomes_instructions() {
+        let backend = GoBackend;
+        let code = "package main\n\nfunc Sum(xs []int) int {\n\t// TODO: skip negative values\n\tpanic(\"not implemented\")\n}\n";
+        let cursor_byte = code.find("panic").unwrap();
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(
+            info.instructions,
+            Some("TODO: skip negative values".to_string())
+        );
+    }
+}
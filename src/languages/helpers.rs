@@ -19,7 +19,7 @@ pub fn extract_signature(node: &Node, source: &str) -> String {
 }
 
 /// Recursive helper to find a "body" field anywhere inside a node
-fn find_body_recursive(node: Node) -> Option<Node> {
+pub fn find_body_recursive(node: Node) -> Option<Node> {
     if let Some(body) = node.child_by_field_name("body") {
         return Some(body);
     }
@@ -44,7 +44,7 @@ pub fn extract_doc_comment(
 
     while let Some(sibling) = current {
         let kind = sibling.kind();
-        if kind == "line_comment" || kind == "comment" {
+        if matches!(kind, "line_comment" | "block_comment" | "comment") {
             let text = text_for(source, &sibling).trim().to_string();
 
             if text.starts_with(comment_prefix) {
@@ -75,11 +75,242 @@ pub fn extract_doc_comment(
     }
 }
 
+/// Finds the innermost node of one of the given kinds that contains the cursor
+pub fn find_innermost_at<'t>(
+    root: Node<'t>,
+    cursor_byte: usize,
+    kinds: &[&str],
+) -> Option<Node<'t>> {
+    let mut found = None;
+    let mut current = Some(root);
+
+    while let Some(node) = current {
+        if kinds.contains(&node.kind()) {
+            found = Some(node);
+        }
+        let mut cursor = node.walk();
+        current = node.children(&mut cursor).find(|child| {
+            child.start_byte() <= cursor_byte && cursor_byte <= child.end_byte()
+        });
+    }
+
+    found
+}
+
+/// Collects every node of one of the given kinds, in document order
+pub fn collect_nodes<'t>(root: Node<'t>, kinds: &[&str]) -> Vec<Node<'t>> {
+    let mut nodes = Vec::new();
+    let mut stack = vec![root];
+
+    while let Some(node) = stack.pop() {
+        if kinds.contains(&node.kind()) {
+            nodes.push(node);
+        }
+        let mut cursor = node.walk();
+        let children: Vec<_> = node.children(&mut cursor).collect();
+        stack.extend(children.into_iter().rev());
+    }
+
+    nodes
+}
+
+/// Indents every line of a generated body, starting each one on a new line.
+/// Used for languages whose blocks are delimited by indentation.
+pub fn indent_body(body: &str, indent: &str) -> String {
+    body.lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                "\n".to_string()
+            } else {
+                format!("\n{indent}{line}")
+            }
+        })
+        .collect()
+}
+
 /// Checks if a function body contains only whitespace and braces
 pub fn is_empty_body(body_text: &str) -> bool {
     body_text.chars().all(|c| c.is_whitespace() || c == '{' || c == '}')
 }
 
+/// A statement that stands in for a function body until it is implemented.
+/// Stubs like `todo!()` keep the code compiling, or running, until the body
+/// is generated, so a body holding one and comments at most counts as empty
+/// and is replaced whole. Each backend lists the stubs of its language.
+pub enum Placeholder {
+    /// A statement or expression of the given node kind, e.g. `pass`
+    Kind(&'static str),
+    /// A call or macro invocation by name, e.g. `todo!()`. With a message,
+    /// one of its string arguments must contain it.
+    Call { name: &'static str, message: Option<&'static str> },
+    /// A thrown or raised exception by class name, e.g.
+    /// `throw new Error("not implemented")`
+    Throw { class: &'static str, message: Option<&'static str> },
+    /// An expression with exactly this text, whitespace aside, e.g.
+    /// `assert(0)`
+    Exact(&'static str),
+}
+
+/// Checks if a body node holds only comments and at most one placeholder
+/// statement. The comments are instructions for the model
+/// (see [`extract_body_instructions`]).
+pub fn is_placeholder_body(
+    body: &Node,
+    source: &str,
+    placeholders: &[Placeholder],
+) -> bool {
+    is_stub_body(body, |s| is_placeholder(s, source, placeholders))
+}
+
+/// Like [`is_placeholder_body`], with the placeholder statements recognised
+/// by the given predicate
+pub fn is_stub_body(
+    body: &Node,
+    is_placeholder: impl Fn(&Node) -> bool,
+) -> bool {
+    let children = body_children(body);
+    let statements: Vec<&Node> =
+        children.iter().filter(|c| !is_comment(c)).collect();
+
+    !children.is_empty()
+        && statements.len() <= 1
+        && statements.iter().all(|s| is_placeholder(s))
+}
+
+/// Collects the comments inside an otherwise empty body as instructions
+/// for the model, dropping bare `TODO` markers
+pub fn extract_body_instructions(body: &Node, source: &str) -> Option<String> {
+    comments_as_instructions(
+        body_children(body).iter().filter(|c| is_comment(c)),
+        source,
+    )
+}
+
+/// End of the last comment inside a body
+pub fn body_comments_end(body: &Node) -> Option<usize> {
+    body_children(body)
+        .iter()
+        .filter(|c| is_comment(c))
+        .map(|c| c.end_byte())
+        .max()
+}
+
+/// Joins the cleaned text of the given comments into instructions
+pub fn comments_as_instructions<'t>(
+    comments: impl IntoIterator<Item = &'t Node<'t>>,
+    source: &str,
+) -> Option<String> {
+    let lines: Vec<String> = comments
+        .into_iter()
+        .map(|c| clean_comment(text_for(source, c)))
+        .filter(|text| {
+            !text.is_empty()
+                && !text.trim_end_matches(':').eq_ignore_ascii_case("todo")
+        })
+        .collect();
+
+    if lines.is_empty() { None } else { Some(lines.join("\n")) }
+}
+
+/// Statements and comments of a body. Some grammars (e.g. Go) wrap the
+/// statements in a list node, which is flattened.
+fn body_children<'t>(body: &Node<'t>) -> Vec<Node<'t>> {
+    let mut cursor = body.walk();
+    body.named_children(&mut cursor)
+        .flat_map(|child| {
+            if child.kind() == "statement_list" {
+                let mut inner = child.walk();
+                child.named_children(&mut inner).collect()
+            } else {
+                vec![child]
+            }
+        })
+        .collect()
+}
+
+/// Checks if a single statement matches one of the placeholders
+pub fn is_placeholder(
+    statement: &Node,
+    source: &str,
+    placeholders: &[Placeholder],
+) -> bool {
+    let node = match statement.kind() {
+        "expression_statement" => match statement.named_child(0) {
+            Some(inner) => inner,
+            None => return false,
+        },
+        _ => *statement,
+    };
+
+    placeholders.iter().any(|placeholder| match placeholder {
+        Placeholder::Kind(kind) => node.kind() == *kind,
+        Placeholder::Call { name, message } => {
+            matches!(node.kind(), "macro_invocation" | "call_expression" | "call")
+                && callee_name(&node, source) == Some(*name)
+                && message.is_none_or(|m| contains_message(&node, source, m))
+        }
+        Placeholder::Exact(text) => {
+            text_for(source, &node).split_whitespace().collect::<String>()
+                == *text
+        }
+        Placeholder::Throw { class, message } => {
+            let mut cursor = node.walk();
+            matches!(
+                node.kind(),
+                "throw_statement" | "throw_expression" | "raise_statement"
+            )
+                && node
+                    .named_children(&mut cursor)
+                    .find(|c| !is_comment(c))
+                    .is_some_and(|thrown| {
+                        callee_name(&thrown, source) == Some(*class)
+                    })
+                && message.is_none_or(|m| contains_message(&node, source, m))
+        }
+    })
+}
+
+pub fn is_comment(node: &Node) -> bool {
+    matches!(node.kind(), "line_comment" | "block_comment" | "comment")
+}
+
+/// Strips the comment markers from a line or block comment
+pub fn clean_comment(text: &str) -> String {
+    let text = text.trim();
+    if text.starts_with("/*") {
+        return clean_jsdoc(text.trim_start_matches("/*"));
+    }
+    text.trim_start_matches(['/', '!', '#']).trim().to_string()
+}
+
+/// Unqualified name of whatever a call, macro or constructor invokes
+fn callee_name<'a>(node: &Node, source: &'a str) -> Option<&'a str> {
+    let callee = match node.kind() {
+        "macro_invocation" => node.child_by_field_name("macro")?,
+        "new_expression" => node.child_by_field_name("constructor")?,
+        "object_creation_expression" => node.child_by_field_name("type")?,
+        // Kotlin's calls have no `function` field
+        "call_expression" | "call" => node
+            .child_by_field_name("function")
+            .or_else(|| node.named_child(0))?,
+        _ => *node,
+    };
+    text_for(source, &callee).rsplit([':', '.']).next()
+}
+
+/// Checks if any string literal inside the node contains the message,
+/// ignoring case
+fn contains_message(node: &Node, source: &str, message: &str) -> bool {
+    if node.kind().contains("string") {
+        return text_for(source, node)
+            .to_lowercase()
+            .contains(&message.to_lowercase());
+    }
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor)
+        .any(|child| contains_message(&child, source, message))
+}
+
 fn clean_jsdoc(text: &str) -> String {
     text.trim_start_matches("/**")
         .trim_end_matches("*/")
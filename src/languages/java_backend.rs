@@ -0,0 +1,281 @@
+use crate::languages::helpers::{
+    Placeholder, body_comments_end, collect_nodes, extract_body_instructions,
+    extract_doc_comment, extract_signature, find_innermost_at, is_empty_body,
+    is_placeholder_body, text_for,
+};
+use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
+use tree_sitter::{Node, Parser};
+use tree_sitter_java;
+
+const METHOD_KINDS: &[&str] = &["method_declaration", "constructor_declaration"];
+
+const TYPE_KINDS: &[&str] = &[
+    "class_declaration",
+    "enum_declaration",
+    "interface_declaration",
+    "record_declaration",
+];
+
+const PLACEHOLDERS: &[Placeholder] = &[
+    Placeholder::Throw { class: "UnsupportedOperationException", message: None },
+    Placeholder::Throw { class: "RuntimeException", message: Some("not implemented") },
+];
+
+pub struct JavaBackend;
+
+impl LanguageStandard for JavaBackend {
+    fn find_empty_function_at_cursor(
+        &self,
+        source_code: &str,
+        cursor_byte: usize,
+    ) -> Option<FunctionInfo> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_java::LANGUAGE.into())
+            .expect("Failed to load Java grammar");
+
+        let tree = parser.parse(source_code, None)?;
+        let node =
+            find_innermost_at(tree.root_node(), cursor_byte, METHOD_KINDS)?;
+
+        method_info(node, source_code)
+    }
+
+    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
+        let mut parser = Parser::new();
+
+        parser
+            .set_language(&tree_sitter_java::LANGUAGE.into())
+            .expect("Error loading Java grammar");
+
+        let tree = parser.parse(source_code, None).unwrap();
+
+        collect_nodes(tree.root_node(), METHOD_KINDS)
+            .into_iter()
+            .filter_map(|node| method_info(node, source_code))
+            .collect()
+    }
+}
+
+//---------------------- Backend specific helpers -----------------------------
+
+fn method_info(node: Node, source: &str) -> Option<FunctionInfo> {
+    // Abstract and interface methods have no body at all
+    let body_node = node.child_by_field_name("body")?;
+    if !is_empty_body(text_for(source, &body_node))
+        && !is_placeholder_body(&body_node, source, PLACEHOLDERS)
+    {
+        return None;
+    }
+
+    let method_signature = extract_signature(&node, source);
+    let signature = match enclosing_type_context(node, source) {
+        Some(context) => format!("{context}    {method_signature}"),
+        None => method_signature,
+    };
+
+    Some(FunctionInfo {
+        signature,
+        doc_comment: extract_doc_comment(&node, source, "/**"),
+        instructions: extract_body_instructions(&body_node, source),
+        instructions_end: body_comments_end(&body_node),
+        start_byte: body_node.start_byte() + 1,
+        end_byte: body_node.end_byte() - 1,
+        indent: None,
+    })
+}
+
+/// Renders the header of the innermost enclosing type together with its
+/// fields (and constants for enums), so the model knows what the method
+/// body can touch. The methods of an anonymous class get that class, e.g.
+/// `new Runnable()`, rather than the type declaring it.
+fn enclosing_type_context(node: Node, source: &str) -> Option<String> {
+    let mut current = node.parent();
+    let (header, body) = loop {
+        let n = current?;
+        if TYPE_KINDS.contains(&n.kind()) {
+            let body = n.child_by_field_name("body")?;
+            break (extract_signature(&n, source), body);
+        }
+        if let Some(creation) = n.parent()
+            && n.kind() == "class_body"
+            && creation.kind() == "object_creation_expression"
+        {
+            let header = source[creation.start_byte()..n.start_byte()].trim();
+            break (header.to_string(), n);
+        }
+        current = n.parent();
+    };
+
+    let mut context = format!("{header} {{\n");
+
+    let mut cursor = body.walk();
+    let constants: Vec<&str> = body
+        .named_children(&mut cursor)
+        .filter(|c| c.kind() == "enum_constant")
+        .map(|c| text_for(source, &c))
+        .collect();
+    if !constants.is_empty() {
+        context.push_str(&format!("    {};\n", constants.join(", ")));
+    }
+
+    for member in members(body) {
+        if matches!(member.kind(), "field_declaration" | "constant_declaration") {
+            context.push_str(&format!("    {}\n", text_for(source, &member)));
+        }
+    }
+
+    context.push('\n');
+    Some(context)
+}
+
+/// Member declarations of a type body. Enum members other than the constants
+/// live in a nested `enum_body_declarations` node.
+fn members(body: Node) -> Vec<Node> {
+    let mut cursor = body.walk();
+    body.named_children(&mut cursor)
+        .flat_map(|child| {
+            if child.kind() == "enum_body_declarations" {
+                let mut inner = child.walk();
+                child.named_children(&mut inner).collect()
+            } else {
+                vec![child]
+            }
+        })
+        .collect()
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_method_in_class() {
+        let backend = JavaBackend;
+        let code = r#"
+public class Counter extends Base {
+    private int count;
+    private final String name = "c";
+
+    /**
+     * Increments the counter by the given step
+     */
+    public int increment(int step) {
+    }
+
+    public String name() { return name; }
+}"#;
+        let cursor_byte = code.find("step) {").unwrap() + 8;
+
+        let result = backend.find_empty_function_at_cursor(code, cursor_byte);
+
+        assert!(result.is_some(), "Should find the empty method");
+        let info = result.unwrap();
+
+        assert_eq!(
+            info.signature,
+            "public class Counter extends Base {\n    \
+             private int count;\n    \
+             private final String name = \"c\";\n\n    \
+             public int increment(int step)"
+        );
+        assert_eq!(
+            info.doc_comment,
+            Some("Increments the counter by the given step".to_string())
+        );
+
+        let body_content = &code[info.start_byte..info.end_byte];
+        assert!(body_content.trim().is_empty());
+    }
+
+    #[test]
+    fn test_find_enum_method() {
+        let backend = JavaBackend;
+        let code = "enum Color { RED, GREEN; private int rgb; int rgb() {} }";
+        let cursor_byte = code.find("{}").unwrap() + 1;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(
+            info.signature,
+            "enum Color {\n    RED, GREEN;\n    private int rgb;\n\n    int rgb()"
+        );
+    }
+
+    #[test]
+    fn test_find_interface_default_method() {
+        let backend = JavaBackend;
+        let code = r#"
+interface Shape {
+    double area();
+
+    /** Describes the shape */
+    default String describe() {
+    }
+}"#;
+        let cursor_byte = code.rfind('{').unwrap() + 1;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert!(info.signature.starts_with("interface Shape {"));
+        assert!(info.signature.ends_with("default String describe()"));
+        assert_eq!(info.doc_comment, Some("Describes the shape".to_string()));
+    }
+
+    #[test]
+    fn test_anonymous_class_method_gets_its_own_context() {
+        let backend = JavaBackend;
+        let code = r#"
+class Scheduler {
+    private int pending;
+
+    void start() {
+        Runnable task = new Runnable() {
+            private int runs;
+
+            public void run() {
+            }
+        };
+    }
+}"#;
+        let cursor_byte = code.find("run() {").unwrap() + 7;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(
+            info.signature,
+            "new Runnable() {\n    private int runs;\n\n    public void run()"
+        );
+    }
+
+    #[test]
+    fn test_ignores_abstract_and_populated_methods() {
+        let backend = JavaBackend;
+        let code = r#"
+abstract class Base {
+    abstract int size();
+    int twice() { return size() * 2; }
+    Base() {}
+}"#;
+
+        let funcs = backend.find_empty_functions(code);
+        assert_eq!(funcs.len(), 1);
+        assert!(funcs[0].signature.ends_with("Base()"));
+    }
+
+    #[test]
+    fn test_throw_placeholder_counts_as_empty() {
+        let backend = JavaBackend;
+        let code = r#"
+class Repo {
+    int count() { throw new UnsupportedOperationException(); }
+    int size() { throw new IllegalStateException("closed"); }
+}"#;
+
+        let funcs = backend.find_empty_functions(code);
+        assert_eq!(funcs.len(), 1);
+        assert!(funcs[0].signature.ends_with("int count()"));
+    }
+}
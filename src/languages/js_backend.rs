@@ -1,102 +1,18 @@
-use crate::languages::helpers::{
-    extract_doc_comment, extract_signature, is_empty_body, text_for,
-};
-use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
-use tree_sitter::Parser;
+use crate::languages::query_backend::{QueryBackend, QueryConfig};
 use tree_sitter_javascript;
 
-pub struct JsBackend;
-
-impl LanguageStandard for JsBackend {
-    fn find_empty_function_at_cursor(
-        &self,
-        source_code: &str,
-        cursor_byte: usize,
-    ) -> Option<FunctionInfo> {
-        let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_javascript::LANGUAGE.into())
-            .expect("Failed to load Javascript grammar");
-
-        let tree = parser.parse(source_code, None)?;
-        let root = tree.root_node();
-        let mut cursor = root.walk();
-
-        for node in root.children(&mut cursor) {
-            let kind = node.kind();
-            if kind != "function_declaration" && kind != "method_definition" {
-                continue;
-            }
-
-            // Check if the cursor is inside the function
-            if cursor_byte < node.start_byte() || cursor_byte > node.end_byte()
-            {
-                continue;
-            }
-
-            // Check if empty
-            let body = match node.child_by_field_name("body") {
-                Some(body_node) => text_for(source_code, &body_node),
-                None => continue,
-            };
-            if !is_empty_body(body) {
-                continue;
-            }
-
-            let body_node = node.child_by_field_name("body").unwrap();
-
-            let signature = extract_signature(&node, source_code);
-            let doc_comment = extract_doc_comment(&node, source_code, "/**");
-
-            return Some(FunctionInfo {
-                signature,
-                doc_comment,
-                start_byte: body_node.start_byte() + 1,
-                end_byte: body_node.end_byte() - 1,
-            });
-        }
-
-        None
-    }
-
-    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
-        let mut parser = Parser::new();
-
-        parser
-            .set_language(&tree_sitter_javascript::LANGUAGE.into())
-            .expect("Error loading Javascript grammar");
-
-        let tree = parser.parse(source_code, None).unwrap();
-        let root_node = tree.root_node();
-        let mut cursor = root_node.walk();
-        let mut funcs = Vec::new();
-
-        for node in root_node.children(&mut cursor) {
-            if node.kind() != "function_item" {
-                continue;
-            }
-
-            let body = match node.child_by_field_name("body") {
-                Some(body_node) => text_for(source_code, &body_node),
-                None => continue,
-            };
-            if !is_empty_body(body) {
-                continue;
-            }
-
-            let signature = extract_signature(&node, source_code);
-            let doc_comment = extract_doc_comment(&node, source_code, "/**");
-
-            funcs.push(FunctionInfo {
-                signature,
-                doc_comment,
-                start_byte: node.start_byte(),
-                end_byte: node.end_byte(),
-            });
-        }
-
-        funcs
-    }
+/// JavaScript backend, driven by `queries/javascript.scm`
+pub fn js_backend() -> QueryBackend {
+    QueryBackend::new(
+        tree_sitter_javascript::LANGUAGE.into(),
+        include_str!("../../queries/javascript.scm"),
+        QueryConfig {
+            doc_prefixes: vec!["/**".to_string()],
+            field_kinds: vec!["field_definition".to_string()],
+            field_terminator: Some(";".to_string()),
+        },
+    )
+    .expect("Invalid JavaScript query")
 }
 
 //-----------------------------Unit Tests--------------------------------------
@@ -104,10 +20,11 @@ impl LanguageStandard for JsBackend {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::languages::language_standard::LanguageStandard;
 
     #[test]
     fn test_find_simple_empty_function() {
-        let backend = JsBackend;
+        let backend = js_backend();
         let code = r#"
 /**
  * This is a test function
@@ -130,7 +47,7 @@ function helloWorld(name) {
 
     #[test]
     fn test_find_async_function() {
-        let backend = JsBackend;
+        let backend = js_backend();
         let code = r#"
 /**
  * Multi-line doc
@@ -147,7 +64,7 @@ async function fetchData(url) {
 
     #[test]
     fn test_ignores_populated_function() {
-        let backend = JsBackend;
+        let backend = js_backend();
         let code = r#"function hasCode() { console.log("hi"); }"#;
         let cursor_byte = code.find('c').unwrap();
         
@@ -157,11 +74,62 @@ async function fetchData(url) {
 
     #[test]
     fn test_cursor_outside_function() {
-        let backend = JsBackend;
+        let backend = js_backend();
         let code = "function empty() {} \n // cursor is here";
         let cursor_byte = code.len() - 1;
         
         let result = backend.find_empty_function_at_cursor(code, cursor_byte);
         assert!(result.is_none(), "Should ignore if cursor is outside bounds");
     }
+
+    #[test]
+    fn test_find_object_literal_method() {
+        let backend = js_backend();
+        let code = "const api = {\n  fetch(url) {\n  },\n  parse: (text) => {},\n};";
+        let cursor_byte = code.find("url) {").unwrap() + 7;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+        assert_eq!(info.signature, "fetch(url)");
+
+        let cursor_byte = code.find("=> {").unwrap() + 4;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+        assert_eq!(info.signature, "parse: (text) =>");
+    }
+
+    #[test]
+    fn test_find_nested_function() {
+        let backend = js_backend();
+        let code = r#"
+function outer(items) {
+    /** Checks one item */
+    function check(item) {
+    }
+    return items.filter(check);
+}"#;
+        let cursor_byte = code.find("item) {").unwrap() + 7;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.signature, "function check(item)");
+        assert_eq!(info.doc_comment, Some("Checks one item".to_string()));
+
+        let funcs = backend.find_empty_functions(code);
+        assert_eq!(funcs.len(), 1);
+    }
+
+    #[test]
+    fn test_throw_placeholder_counts_as_empty() {
+        let backend = js_backend();
+        let code = r#"
+function a() { throw new Error("Not implemented"); }
+function b() { throw new Error("TODO"); }
+function c() { throw new Error("bad input"); }
+"#;
+
+        let funcs = backend.find_empty_functions(code);
+        let names: Vec<_> =
+            funcs.iter().map(|f| f.signature.as_str()).collect();
+        assert_eq!(names, vec!["function a()", "function b()"]);
+    }
 }
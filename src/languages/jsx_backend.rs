@@ -1,120 +1,18 @@
-use crate::languages::helpers::{
-    extract_doc_comment, extract_signature, is_empty_body, text_for,
-};
-use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
-use tree_sitter::Parser;
+use crate::languages::query_backend::{QueryBackend, QueryConfig};
 use tree_sitter_javascript;
 
-pub struct JsxBackend;
-
-impl LanguageStandard for JsxBackend {
-    fn find_empty_function_at_cursor(
-        &self,
-        source_code: &str,
-        cursor_byte: usize,
-    ) -> Option<FunctionInfo> {
-        let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_javascript::LANGUAGE.into())
-            .expect("Failed to load Typescript grammar");
-
-        let tree = parser.parse(source_code, None)?;
-        let root = tree.root_node();
-        let mut cursor = root.walk();
-
-        for node in root.children(&mut cursor) {
-            let kind = node.kind();
-
-            // Check bounds first to save processing
-            if cursor_byte < node.start_byte() || cursor_byte > node.end_byte()
-            {
-                continue;
-            }
-            // Identify the "real" function node
-            let target_node = match kind {
-                "function_declaration" | "method_definition" => Some(node),
-                "export_statement" | "lexical_declaration" => {
-                    find_arrow_recursive(node)
-                }
-                _ => None,
-            };
-
-            if let Some(func_node) = target_node {
-                if let Some(body_node) = func_node.child_by_field_name("body") {
-                    let body_text = text_for(source_code, &body_node);
-
-                    if is_empty_body(body_text) {
-                        return Some(FunctionInfo {
-                            signature: extract_signature(&node, source_code),
-                            doc_comment: extract_doc_comment(
-                                &node,
-                                source_code,
-                                "/**",
-                            ),
-                            start_byte: body_node.start_byte() + 1,
-                            end_byte: body_node.end_byte() - 1,
-                        });
-                    }
-                }
-            }
-        }
-
-        None
-    }
-
-    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
-        let mut parser = Parser::new();
-
-        parser
-            .set_language(&tree_sitter_javascript::LANGUAGE.into())
-            .expect("Error loading Javascriptreact grammar");
-
-        let tree = parser.parse(source_code, None).unwrap();
-        let root_node = tree.root_node();
-        let mut cursor = root_node.walk();
-        let mut funcs = Vec::new();
-
-        for node in root_node.children(&mut cursor) {
-            if node.kind() != "function_item" {
-                continue;
-            }
-
-            let body = match node.child_by_field_name("body") {
-                Some(body_node) => text_for(source_code, &body_node),
-                None => continue,
-            };
-            if !is_empty_body(body) {
-                continue;
-            }
-
-            let signature = extract_signature(&node, source_code);
-            let doc_comment = extract_doc_comment(&node, source_code, "/**");
-
-            funcs.push(FunctionInfo {
-                signature,
-                doc_comment,
-                start_byte: node.start_byte(),
-                end_byte: node.end_byte(),
-            });
-        }
-
-        funcs
-    }
-}
-
-//---------------------- Backend specific helpers -----------------------------
-
-fn find_arrow_recursive(node: tree_sitter::Node) -> Option<tree_sitter::Node> {
-    if node.kind() == "arrow_function" {
-        return Some(node);
-    }
-    let mut cursor = node.walk();
-    for child in node.children(&mut cursor) {
-        if let Some(found) = find_arrow_recursive(child) {
-            return Some(found);
-        }
-    }
-    None
+/// JSX backend, driven by `queries/javascript.scm`
+pub fn jsx_backend() -> QueryBackend {
+    QueryBackend::new(
+        tree_sitter_javascript::LANGUAGE.into(),
+        include_str!("../../queries/javascript.scm"),
+        QueryConfig {
+            doc_prefixes: vec!["/**".to_string()],
+            field_kinds: vec!["field_definition".to_string()],
+            field_terminator: Some(";".to_string()),
+        },
+    )
+    .expect("Invalid JSX query")
 }
 
 //-----------------------------Unit Tests--------------------------------------
@@ -122,10 +20,11 @@ fn find_arrow_recursive(node: tree_sitter::Node) -> Option<tree_sitter::Node> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::languages::language_standard::LanguageStandard;
 
     #[test]
     fn test_find_simple_empty_function() {
-        let backend = JsxBackend;
+        let backend = jsx_backend();
         let code = r#"
 /**
  * This is a test function
@@ -148,7 +47,7 @@ function helloWorld(name) {
 
     #[test]
     fn test_find_arrow_function() {
-        let backend = JsxBackend;
+        let backend = jsx_backend();
         let code = r#"
 /**
  * Arrow function test
@@ -168,7 +67,7 @@ export const myFunc = (x) => {
 
     #[test]
     fn test_ignores_populated_function() {
-        let backend = JsxBackend;
+        let backend = jsx_backend();
         let code = r#"function hasCode() { console.log("hi"); }"#;
         let cursor_byte = code.find('c').unwrap();
         
@@ -178,11 +77,35 @@ export const myFunc = (x) => {
 
     #[test]
     fn test_cursor_outside_function() {
-        let backend = JsxBackend;
+        let backend = jsx_backend();
         let code = "function empty() {} \n // cursor is here";
         let cursor_byte = code.len() - 1;
         
         let result = backend.find_empty_function_at_cursor(code, cursor_byte);
         assert!(result.is_none(), "Should ignore if cursor is outside bounds");
     }
+
+    #[test]
+    fn test_find_arrow_class_field() {
+        let backend = jsx_backend();
+        let code = r#"
+class Form extends Component {
+    state = { value: "" };
+
+    /** Handles submission */
+    handleSubmit = (event) => {
+    };
+}"#;
+        let cursor_byte = code.find("=> {").unwrap() + 4;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(
+            info.signature,
+            "class Form extends Component {\n    \
+             state = { value: \"\" };\n\n    \
+             handleSubmit = (event) =>"
+        );
+        assert_eq!(info.doc_comment, Some("Handles submission".to_string()));
+    }
 }
@@ -0,0 +1,268 @@
+use crate::languages::helpers::{
+    Placeholder, body_comments_end, collect_nodes, extract_body_instructions,
+    extract_doc_comment, find_innermost_at, is_empty_body, is_placeholder_body,
+    text_for,
+};
+use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
+use tree_sitter::{Node, Parser};
+use tree_sitter_kotlin_ng;
+
+const FUNCTION_KINDS: &[&str] = &["function_declaration"];
+
+const TYPE_KINDS: &[&str] = &[
+    "class_declaration",
+    "object_declaration",
+    "companion_object",
+    "object_literal",
+];
+
+const PLACEHOLDERS: &[Placeholder] = &[
+    Placeholder::Call { name: "TODO", message: None },
+    Placeholder::Throw { class: "NotImplementedError", message: None },
+];
+
+pub struct KotlinBackend;
+
+impl LanguageStandard for KotlinBackend {
+    fn find_empty_function_at_cursor(
+        &self,
+        source_code: &str,
+        cursor_byte: usize,
+    ) -> Option<FunctionInfo> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_kotlin_ng::LANGUAGE.into())
+            .expect("Failed to load Kotlin grammar");
+
+        let tree = parser.parse(source_code, None)?;
+        let node =
+            find_innermost_at(tree.root_node(), cursor_byte, FUNCTION_KINDS)?;
+
+        function_info(node, source_code)
+    }
+
+    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
+        let mut parser = Parser::new();
+
+        parser
+            .set_language(&tree_sitter_kotlin_ng::LANGUAGE.into())
+            .expect("Error loading Kotlin grammar");
+
+        let tree = parser.parse(source_code, None).unwrap();
+
+        collect_nodes(tree.root_node(), FUNCTION_KINDS)
+            .into_iter()
+            .filter_map(|node| function_info(node, source_code))
+            .collect()
+    }
+}
+
+//---------------------- Backend specific helpers -----------------------------
+
+fn function_info(node: Node, source: &str) -> Option<FunctionInfo> {
+    // Abstract and interface functions have no body, and expression bodies
+    // (`fun f() = ...`) have no braces to fill
+    let function_body = child_of_kind(node, "function_body")?;
+    let block = child_of_kind(function_body, "block")?;
+    if !is_empty_body(text_for(source, &block))
+        && !is_placeholder_body(&block, source, PLACEHOLDERS)
+    {
+        return None;
+    }
+
+    let function_signature = header(node, function_body, source);
+    let signature = match enclosing_type_context(node, source) {
+        Some(context) => format!("{context}    {function_signature}"),
+        None => function_signature,
+    };
+
+    Some(FunctionInfo {
+        signature,
+        doc_comment: extract_doc_comment(&node, source, "/**"),
+        instructions: extract_body_instructions(&block, source),
+        instructions_end: body_comments_end(&block),
+        start_byte: block.start_byte() + 1,
+        end_byte: block.end_byte() - 1,
+        indent: None,
+    })
+}
+
+/// Renders the header of the innermost enclosing class, object or object
+/// expression together with its properties, so the model knows what the
+/// function body can touch
+fn enclosing_type_context(node: Node, source: &str) -> Option<String> {
+    let mut current = node.parent();
+    while let Some(n) = current {
+        if TYPE_KINDS.contains(&n.kind()) {
+            break;
+        }
+        current = n.parent();
+    }
+    let type_node = current?;
+    let body = child_of_kind(type_node, "class_body")
+        .or_else(|| child_of_kind(type_node, "enum_class_body"))?;
+
+    let mut context = format!("{} {{\n", header(type_node, body, source));
+
+    let mut cursor = body.walk();
+    let entries: Vec<&str> = body
+        .named_children(&mut cursor)
+        .filter(|c| c.kind() == "enum_entry")
+        .map(|c| text_for(source, &c))
+        .collect();
+    if !entries.is_empty() {
+        context.push_str(&format!("    {};\n", entries.join(", ")));
+    }
+
+    let mut cursor = body.walk();
+    for member in body.named_children(&mut cursor) {
+        if member.kind() == "property_declaration" {
+            context.push_str(&format!("    {}\n", text_for(source, &member)));
+        }
+    }
+
+    context.push('\n');
+    Some(context)
+}
+
+/// Everything of a declaration before its body. The Kotlin grammar has no
+/// `body` fields, so [`extract_signature`] cannot find it.
+///
+/// [`extract_signature`]: crate::languages::helpers::extract_signature
+fn header(node: Node, body: Node, source: &str) -> String {
+    source[node.start_byte()..body.start_byte()].trim().to_string()
+}
+
+fn child_of_kind<'t>(node: Node<'t>, kind: &str) -> Option<Node<'t>> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).find(|c| c.kind() == kind)
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_method_in_class() {
+        let backend = KotlinBackend;
+        let code = r#"
+class Counter(val start: Int) : Base() {
+    private var count: Int = 0
+    val name = "c"
+
+    /**
+     * Increments the counter by the given step
+     */
+    fun increment(step: Int): Int {
+    }
+
+    fun name(): String = name
+}"#;
+        let cursor_byte = code.find("Int {").unwrap() + 5;
+
+        let result = backend.find_empty_function_at_cursor(code, cursor_byte);
+
+        assert!(result.is_some(), "Should find the empty method");
+        let info = result.unwrap();
+
+        assert_eq!(
+            info.signature,
+            "class Counter(val start: Int) : Base() {\n    \
+             private var count: Int = 0\n    \
+             val name = \"c\"\n\n    \
+             fun increment(step: Int): Int"
+        );
+        assert_eq!(
+            info.doc_comment,
+            Some("Increments the counter by the given step".to_string())
+        );
+
+        let body_content = &code[info.start_byte..info.end_byte];
+        assert!(body_content.trim().is_empty());
+    }
+
+    #[test]
+    fn test_find_enum_and_interface_methods() {
+        let backend = KotlinBackend;
+        let code = r#"
+enum class Color(val rgb: Int) {
+    RED(1), GREEN(2);
+
+    fun hex(): String {
+    }
+}
+
+interface Shape {
+    fun area(): Double
+
+    fun describe(): String {
+        throw NotImplementedError()
+    }
+}"#;
+
+        let funcs = backend.find_empty_functions(code);
+        assert_eq!(funcs.len(), 2);
+        assert_eq!(
+            funcs[0].signature,
+            "enum class Color(val rgb: Int) {\n    RED(1), GREEN(2);\n\n    \
+             fun hex(): String"
+        );
+        assert!(funcs[1].signature.starts_with("interface Shape {"));
+        assert!(funcs[1].signature.ends_with("fun describe(): String"));
+    }
+
+    #[test]
+    fn test_placeholders_and_instructions() {
+        let backend = KotlinBackend;
+        let code = r#"
+object Registry {
+    fun all(): List<Int> {
+        // newest first
+        TODO("later")
+    }
+
+    fun count(): Int {
+        return 0
+    }
+}
+
+fun <T> List<T>.second(): T {
+    error("unset")
+}"#;
+
+        let funcs = backend.find_empty_functions(code);
+        assert_eq!(funcs.len(), 1);
+        assert_eq!(
+            funcs[0].signature,
+            "object Registry {\n\n    fun all(): List<Int>"
+        );
+        assert_eq!(funcs[0].instructions, Some("newest first".to_string()));
+    }
+
+    #[test]
+    fn test_method_of_object_expression() {
+        let backend = KotlinBackend;
+        let code = r#"
+class Worker {
+    val jobs = 0
+
+    fun start() {
+        val task = object : Runnable {
+            override fun run() {
+            }
+        }
+    }
+}"#;
+        let cursor_byte = code.find("run() {").unwrap() + 7;
+
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(
+            info.signature,
+            "object : Runnable {\n\n    override fun run()"
+        );
+    }
+}
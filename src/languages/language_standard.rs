@@ -3,8 +3,16 @@
 pub struct FunctionInfo {
     pub signature: String,
     pub doc_comment: Option<String>,
+    /// Guidance written as comments inside the otherwise empty body
+    pub instructions: Option<String>,
+    /// End of the last comment in the body. Fill-in-the-middle prompts keep
+    /// the body up to there, so the model still reads the instructions.
+    pub instructions_end: Option<usize>,
     pub start_byte: usize,
     pub end_byte: usize,
+    /// Indentation applied to each generated line, for languages whose
+    /// blocks are delimited by indentation rather than braces
+    pub indent: Option<String>,
 }
 
 /// Trait for language-specific backend implementations
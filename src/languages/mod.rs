@@ -1,7 +1,17 @@
+pub mod c_backend;
+pub mod config;
+pub mod cpp_backend;
+pub mod dynamic;
+pub mod go_backend;
 pub mod helpers;
+pub mod java_backend;
+pub mod kotlin_backend;
 pub mod language_standard;
 pub mod rust_backend;
 pub mod ts_backend;
 pub mod tsx_backend;
 pub mod js_backend;
 pub mod jsx_backend;
+pub mod python_backend;
+pub mod query_backend;
+pub mod registry;
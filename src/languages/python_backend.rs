@@ -0,0 +1,344 @@
+use crate::languages::helpers::{
+    Placeholder, collect_nodes, comments_as_instructions, extract_doc_comment,
+    find_innermost_at, is_comment, is_placeholder, text_for,
+};
+use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
+use tree_sitter::{Node, Parser};
+use tree_sitter_python;
+
+const PLACEHOLDERS: &[Placeholder] = &[
+    Placeholder::Kind("pass_statement"),
+    Placeholder::Kind("ellipsis"),
+    Placeholder::Throw { class: "NotImplementedError", message: None },
+];
+
+pub struct PythonBackend;
+
+impl LanguageStandard for PythonBackend {
+    fn find_empty_function_at_cursor(
+        &self,
+        source_code: &str,
+        cursor_byte: usize,
+    ) -> Option<FunctionInfo> {
+        let mut parser = Parser::new();
+        parser
+            .set_language(&tree_sitter_python::LANGUAGE.into())
+            .expect("Failed to load Python grammar");
+
+        let tree = parser.parse(source_code, None)?;
+        let node = find_innermost_at(
+            tree.root_node(),
+            cursor_byte,
+            &["function_definition"],
+        )?;
+
+        function_info(node, source_code)
+    }
+
+    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
+        let mut parser = Parser::new();
+
+        parser
+            .set_language(&tree_sitter_python::LANGUAGE.into())
+            .expect("Error loading Python grammar");
+
+        let tree = parser.parse(source_code, None).unwrap();
+
+        collect_nodes(tree.root_node(), &["function_definition"])
+            .into_iter()
+            .filter_map(|node| function_info(node, source_code))
+            .collect()
+    }
+}
+
+//---------------------- Backend specific helpers -----------------------------
+
+/// Builds the function info for a `function_definition` whose suite is only
+/// placeholders such as `pass` and `...` and/or a docstring
+fn function_info(node: Node, source: &str) -> Option<FunctionInfo> {
+    let body = node.child_by_field_name("body")?;
+    let mut cursor = body.walk();
+    let statements: Vec<Node> = body.named_children(&mut cursor).collect();
+
+    let docstring = statements.first().copied().filter(|s| is_docstring(*s));
+    let placeholders = &statements[docstring.is_some() as usize..];
+
+    if statements.is_empty()
+        || !placeholders
+            .iter()
+            .all(|s| is_comment(s) || is_placeholder(s, source, PLACEHOLDERS))
+    {
+        return None;
+    }
+
+    // A docstring on the header line makes a one-line suite, after which
+    // no indented block can follow
+    let header_row = body.prev_sibling()?.end_position().row;
+    if docstring.is_some_and(|doc| doc.start_position().row == header_row) {
+        return None;
+    }
+
+    // Comments on their own lines before the first statement are attached
+    // to the definition rather than the block, but belong to the suite
+    let mut header_end = body.prev_sibling()?;
+    let mut leading_comments = Vec::new();
+    while header_end.kind() == "comment" {
+        let prev = header_end.prev_sibling()?;
+        if header_end.start_position().row == prev.end_position().row {
+            break;
+        }
+        leading_comments.push(header_end);
+        header_end = prev;
+    }
+    leading_comments.reverse();
+
+    // The replaceable range starts after the docstring, or after the header
+    // (the `:` or a trailing comment on the header line) when there is none
+    let start_byte = match docstring {
+        Some(doc) => doc.end_byte(),
+        None => header_end.end_byte(),
+    };
+
+    // Decorators and preceding comments belong to the wrapping node
+    let outer = match node.parent() {
+        Some(p) if p.kind() == "decorated_definition" => p,
+        _ => node,
+    };
+
+    let doc_comment = match docstring {
+        Some(doc) => Some(clean_docstring(text_for(source, &doc))),
+        None => extract_doc_comment(&outer, source, "#"),
+    };
+
+    let comments: Vec<&Node> = leading_comments
+        .iter()
+        .chain(placeholders.iter().filter(|s| is_comment(s)))
+        .collect();
+
+    Some(FunctionInfo {
+        signature: source[outer.start_byte()..header_end.end_byte()]
+            .trim()
+            .to_string(),
+        doc_comment,
+        instructions: comments_as_instructions(comments.iter().copied(), source),
+        instructions_end: comments.iter().map(|c| c.end_byte()).max(),
+        start_byte,
+        end_byte: body.end_byte(),
+        indent: Some(body_indent(node, statements[0], source)),
+    })
+}
+
+/// Checks if a statement is a bare string literal
+fn is_docstring(statement: Node) -> bool {
+    statement.kind() == "expression_statement"
+        && statement.named_child_count() == 1
+        && statement.named_child(0).is_some_and(|c| c.kind() == "string")
+}
+
+/// Indentation of the suite, derived from its first statement. A suite on
+/// the same line as the header gets one level deeper than the `def`.
+fn body_indent(func: Node, first: Node, source: &str) -> String {
+    if first.start_position().row != func.start_position().row {
+        let line_start = first.start_byte() - first.start_position().column;
+        return source[line_start..first.start_byte()].to_string();
+    }
+
+    let line_start = func.start_byte() - func.start_position().column;
+    let def_indent: String = source[line_start..func.start_byte()]
+        .chars()
+        .take_while(|c| c.is_whitespace())
+        .collect();
+    format!("{def_indent}    ")
+}
+
+/// Strips the prefix, quotes and common indentation from a docstring, the
+/// way `inspect.cleandoc` does: the first line is stripped, the indentation
+/// shared by the other lines removed, and blank lines around the text dropped
+fn clean_docstring(text: &str) -> String {
+    let text = text.trim_start_matches(['r', 'R', 'u', 'U', 'b', 'B', 'f', 'F']);
+    let quote = if text.starts_with("\"\"\"") || text.starts_with("'''") {
+        &text[..3]
+    } else {
+        &text[..1]
+    };
+    let inner = text.trim_start_matches(quote).trim_end_matches(quote);
+
+    let mut lines = inner.lines();
+    let first = lines.next().unwrap_or("").trim();
+    let rest: Vec<&str> = lines.collect();
+    let margin = rest
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let lines: Vec<&str> = std::iter::once(first)
+        .chain(
+            rest.iter()
+                .map(|line| line.get(margin..).unwrap_or("").trim_end()),
+        )
+        .collect();
+    let Some(start) = lines.iter().position(|line| !line.is_empty()) else {
+        return String::new();
+    };
+    let end = lines.iter().rposition(|line| !line.is_empty()).unwrap_or(start);
+
+    lines[start..=end].join("\n")
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_pass_function() {
+        let backend = PythonBackend;
+        let code = r#"
+# Adds two numbers
+def add(a: int, b: int) -> int:
+    pass
+"#;
+        let cursor_byte = code.find("pass").unwrap();
+
+        let result = backend.find_empty_function_at_cursor(code, cursor_byte);
+
+        assert!(result.is_some(), "Should find the empty function");
+        let info = result.unwrap();
+
+        assert_eq!(info.signature, "def add(a: int, b: int) -> int:");
+        assert_eq!(info.doc_comment, Some("Adds two numbers".to_string()));
+        assert_eq!(&code[info.start_byte..info.end_byte], "\n    pass");
+        assert_eq!(info.indent.as_deref(), Some("    "));
+    }
+
+    #[test]
+    fn test_find_docstring_only_method() {
+        let backend = PythonBackend;
+        let code = r#"
+class Greeter:
+    def greet(self, name):
+        """Returns a greeting.
+
+        Uses the given name.
+        """
+"#;
+        let cursor_byte = code.find("Returns").unwrap();
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.signature, "def greet(self, name):");
+        assert_eq!(
+            info.doc_comment,
+            Some("Returns a greeting.\n\nUses the given name.".to_string())
+        );
+        assert_eq!(info.start_byte, info.end_byte);
+        assert!(code[..info.start_byte].ends_with("\"\"\""));
+        assert_eq!(info.indent.as_deref(), Some("        "));
+    }
+
+    #[test]
+    fn test_docstring_keeps_relative_indentation() {
+        let backend = PythonBackend;
+        let code = r#"
+def parse(text):
+    b"""
+    Parses the text.
+
+    Example:
+        parse("1")
+    """
+"#;
+        let info = backend.find_empty_functions(code).remove(0);
+        assert_eq!(
+            info.doc_comment,
+            Some("Parses the text.\n\nExample:\n    parse(\"1\")".to_string())
+        );
+
+        let code = "def f():\n    f'''Formats.'''\n";
+        let info = backend.find_empty_functions(code).remove(0);
+        assert_eq!(info.doc_comment, Some("Formats.".to_string()));
+    }
+
+    #[test]
+    fn test_ignores_docstring_on_header_line() {
+        let backend = PythonBackend;
+        let code = "def f(): \"\"\"Does nothing.\"\"\"\n\ndef g():\n    \"\"\"Does more.\"\"\"\n";
+
+        let funcs = backend.find_empty_functions(code);
+        assert_eq!(funcs.len(), 1);
+        assert_eq!(funcs[0].signature, "def g():");
+    }
+
+    #[test]
+    fn test_find_ellipsis_on_header_line() {
+        let backend = PythonBackend;
+        let code = "@staticmethod\ndef stub(x): ...\n";
+        let cursor_byte = code.find("...").unwrap();
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.signature, "@staticmethod\ndef stub(x):");
+        assert_eq!(&code[info.start_byte..info.end_byte], " ...");
+        assert_eq!(info.indent.as_deref(), Some("    "));
+    }
+
+    #[test]
+    fn test_ignores_populated_function() {
+        let backend = PythonBackend;
+        let code = "def has_code():\n    print(\"hi\")\n";
+        let cursor_byte = code.find("print").unwrap();
+
+        let result = backend.find_empty_function_at_cursor(code, cursor_byte);
+        assert!(result.is_none(), "Should ignore functions with bodies");
+    }
+
+    #[test]
+    fn test_find_empty_functions() {
+        let backend = PythonBackend;
+        let code = "def a():\n    pass\n\ndef b():\n    return 1\n\ndef c(): ...\n";
+
+        let funcs = backend.find_empty_functions(code);
+        let names: Vec<_> =
+            funcs.iter().map(|f| f.signature.as_str()).collect();
+        assert_eq!(names, vec!["def a():", "def c():"]);
+    }
+
+    #[test]
+    fn test_raise_placeholder_counts_as_empty() {
+        let backend = PythonBackend;
+        let code = "def a():\n    # TODO\n    raise NotImplementedError\n\ndef b():\n    raise NotImplementedError(\"later\")\n\ndef c():\n    raise ValueError()\n";
+
+        let funcs = backend.find_empty_functions(code);
+        let names: Vec<_> =
+            funcs.iter().map(|f| f.signature.as_str()).collect();
+        assert_eq!(names, vec!["def a():", "def b():"]);
+        assert_eq!(
+            &code[funcs[0].start_byte..funcs[0].end_byte],
+            "\n    # TODO\n    raise NotImplementedError"
+        );
+    }
+
+    #[test]
+    fn test_comments_become_instructions() {
+        let backend = PythonBackend;
+        let code = r#"
+def search(items, target):  # public API
+    # use binary search
+    # return None on miss
+    pass
+"#;
+        let cursor_byte = code.find("pass").unwrap();
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.signature, "def search(items, target):  # public API");
+        assert_eq!(
+            info.instructions,
+            Some("use binary search\nreturn None on miss".to_string())
+        );
+        assert!(code[info.start_byte..info.end_byte].starts_with("\n    # use"));
+    }
+}
@@ -0,0 +1,391 @@
+use crate::languages::helpers::{
+    body_comments_end, clean_comment, extract_body_instructions,
+    extract_doc_comment, extract_signature, find_body_recursive, is_empty_body,
+    is_stub_body, text_for,
+};
+use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
+use anyhow::{Result, bail};
+use std::collections::{HashMap, HashSet};
+use tree_sitter::{
+    Language, Node, Parser, Query, QueryCursor, StreamingIterator, Tree,
+};
+
+/// Per-language settings for a [`QueryBackend`]
+#[derive(Debug, Clone, Default)]
+pub struct QueryConfig {
+    /// Prefixes of the doc comments preceding a function, tried in order.
+    /// Only used when the query has no `@doc` capture for the function.
+    pub doc_prefixes: Vec<String>,
+    /// Kinds of the member declarations listed under a `@context` header
+    pub field_kinds: Vec<String>,
+    /// Appended to member declarations whose node excludes the terminator
+    pub field_terminator: Option<String>,
+}
+
+/// Language backend driven by a tree-sitter query instead of hand-written
+/// traversal. The query uses these captures:
+///
+/// - `@function`: the function, from which the signature and docs are taken.
+///   When several patterns capture the same `@body`, the outermost
+///   `@function` wins, so wrappers like `export const f = () => {}` can be
+///   matched alongside the bare arrow function.
+/// - `@body`: the body to fill, required in every function pattern
+/// - `@doc`: optional doc comments for the function
+/// - `@context`: optional enclosing class or impl block, whose header and
+///   fields are sent with the signature. A pattern may capture only
+///   `@context` and `@function` to attach it to functions matched elsewhere.
+/// - `@placeholder`: stub statements (e.g. `todo!()`) that count as empty
+pub struct QueryBackend {
+    language: Language,
+    query: Query,
+    config: QueryConfig,
+    function_capture: u32,
+    body_capture: u32,
+    doc_capture: Option<u32>,
+    context_capture: Option<u32>,
+    placeholder_capture: Option<u32>,
+}
+
+/// A function matched by the query, keyed by its body
+struct Candidate<'t> {
+    function: Node<'t>,
+    body: Node<'t>,
+    docs: Vec<Node<'t>>,
+    context: Option<Node<'t>>,
+}
+
+/// Everything the query matched in one source file
+struct Matches<'t> {
+    candidates: Vec<Candidate<'t>>,
+    placeholders: HashSet<usize>,
+}
+
+impl QueryBackend {
+    pub fn new(
+        language: Language,
+        query_source: &str,
+        config: QueryConfig,
+    ) -> Result<Self> {
+        let query = Query::new(&language, query_source)?;
+
+        let Some(function_capture) = query.capture_index_for_name("function")
+        else {
+            bail!("query has no @function capture");
+        };
+        let Some(body_capture) = query.capture_index_for_name("body") else {
+            bail!("query has no @body capture");
+        };
+
+        Ok(QueryBackend {
+            doc_capture: query.capture_index_for_name("doc"),
+            context_capture: query.capture_index_for_name("context"),
+            placeholder_capture: query.capture_index_for_name("placeholder"),
+            language,
+            query,
+            config,
+            function_capture,
+            body_capture,
+        })
+    }
+
+    fn parse(&self, source_code: &str) -> Option<Tree> {
+        let mut parser = Parser::new();
+        parser.set_language(&self.language).ok()?;
+        parser.parse(source_code, None)
+    }
+
+    fn collect_matches<'t>(&self, tree: &'t Tree, source: &str) -> Matches<'t> {
+        let mut by_body: HashMap<usize, Candidate<'t>> = HashMap::new();
+        let mut contexts: HashMap<usize, Node<'t>> = HashMap::new();
+        let mut placeholders = HashSet::new();
+
+        let mut cursor = QueryCursor::new();
+        let mut matches =
+            cursor.matches(&self.query, tree.root_node(), source.as_bytes());
+
+        while let Some(m) = matches.next() {
+            let mut function = None;
+            let mut body = None;
+            let mut context = None;
+            let mut docs = Vec::new();
+
+            for capture in m.captures {
+                let index = Some(capture.index);
+                if index == Some(self.function_capture) {
+                    function = Some(capture.node);
+                } else if index == Some(self.body_capture) {
+                    body = Some(capture.node);
+                } else if index == self.doc_capture {
+                    docs.push(capture.node);
+                } else if index == self.context_capture {
+                    context = Some(capture.node);
+                } else if index == self.placeholder_capture {
+                    placeholders.insert(capture.node.id());
+                }
+            }
+
+            let Some(function) = function else { continue };
+
+            if let Some(context) = context {
+                let entry = contexts.entry(function.id()).or_insert(context);
+                *entry = outermost(*entry, context);
+            }
+
+            let Some(body) = body else { continue };
+            let candidate = by_body.entry(body.id()).or_insert(Candidate {
+                function,
+                body,
+                docs: Vec::new(),
+                context: None,
+            });
+            candidate.function = outermost(candidate.function, function);
+            for doc in docs {
+                if !candidate.docs.contains(&doc) {
+                    candidate.docs.push(doc);
+                }
+            }
+        }
+
+        let mut candidates: Vec<Candidate> = by_body
+            .into_values()
+            .map(|mut c| {
+                c.context = contexts.get(&c.function.id()).copied();
+                c.docs.sort_by_key(|d| d.start_byte());
+                c
+            })
+            .collect();
+        candidates.sort_by_key(|c| c.body.start_byte());
+
+        Matches { candidates, placeholders }
+    }
+
+    fn function_info(
+        &self,
+        candidate: &Candidate,
+        placeholders: &HashSet<usize>,
+        source: &str,
+    ) -> Option<FunctionInfo> {
+        let body = candidate.body;
+        let body_text = text_for(source, &body);
+        let is_stub = is_stub_body(&body, |statement| {
+            placeholders.contains(&statement.id())
+                || (statement.kind() == "expression_statement"
+                    && statement
+                        .named_child(0)
+                        .is_some_and(|c| placeholders.contains(&c.id())))
+        });
+        if !is_empty_body(body_text) && !is_stub {
+            return None;
+        }
+
+        let fn_signature = source
+            [candidate.function.start_byte()..body.start_byte()]
+            .trim()
+            .to_string();
+        let signature = match candidate.context {
+            Some(context) => format!(
+                "{}{fn_signature}",
+                self.context_header(context, candidate.function, source)
+            ),
+            None => fn_signature,
+        };
+
+        let doc_comment = if candidate.docs.is_empty() {
+            self.config.doc_prefixes.iter().find_map(|prefix| {
+                extract_doc_comment(&candidate.function, source, prefix)
+            })
+        } else {
+            Some(
+                candidate
+                    .docs
+                    .iter()
+                    .map(|d| clean_comment(text_for(source, d)))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+            )
+        };
+
+        // Braces delimit the body in most grammars; keep them in place
+        let (start_byte, end_byte) =
+            if body_text.starts_with('{') && body_text.ends_with('}') {
+                (body.start_byte() + 1, body.end_byte() - 1)
+            } else {
+                (body.start_byte(), body.end_byte())
+            };
+
+        Some(FunctionInfo {
+            signature,
+            doc_comment,
+            instructions: extract_body_instructions(&body, source),
+            instructions_end: body_comments_end(&body),
+            start_byte,
+            end_byte,
+            indent: None,
+        })
+    }
+
+    /// Renders the header of the enclosing class or impl block and its
+    /// fields, leaving the function's own line to the caller
+    fn context_header(
+        &self,
+        context: Node,
+        function: Node,
+        source: &str,
+    ) -> String {
+        let mut header = format!("{} {{\n", extract_signature(&context, source));
+
+        let Some(body) = find_body_recursive(context) else {
+            return header + "    ";
+        };
+        let terminator = self.config.field_terminator.as_deref().unwrap_or("");
+
+        let mut cursor = body.walk();
+        let mut has_fields = false;
+        for field in body.named_children(&mut cursor) {
+            if field == function
+                || !self.config.field_kinds.iter().any(|k| k == field.kind())
+            {
+                continue;
+            }
+            let text = text_for(source, &field).trim_end_matches(terminator);
+            header.push_str(&format!("    {text}{terminator}\n"));
+            has_fields = true;
+        }
+
+        if has_fields {
+            header.push('\n');
+        }
+        header + "    "
+    }
+}
+
+impl LanguageStandard for QueryBackend {
+    fn find_empty_function_at_cursor(
+        &self,
+        source_code: &str,
+        cursor_byte: usize,
+    ) -> Option<FunctionInfo> {
+        let tree = self.parse(source_code)?;
+        let matches = self.collect_matches(&tree, source_code);
+
+        // The innermost function around the cursor
+        let candidate = matches
+            .candidates
+            .iter()
+            .filter(|c| {
+                c.function.start_byte() <= cursor_byte
+                    && cursor_byte <= c.function.end_byte()
+            })
+            .min_by_key(|c| c.function.end_byte() - c.function.start_byte())?;
+
+        self.function_info(candidate, &matches.placeholders, source_code)
+    }
+
+    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
+        let Some(tree) = self.parse(source_code) else {
+            return Vec::new();
+        };
+        let matches = self.collect_matches(&tree, source_code);
+
+        matches
+            .candidates
+            .iter()
+            .filter_map(|c| {
+                self.function_info(c, &matches.placeholders, source_code)
+            })
+            .collect()
+    }
+}
+
+//---------------------- Backend specific helpers -----------------------------
+
+/// Returns whichever node spans more of the source
+fn outermost<'t>(a: Node<'t>, b: Node<'t>) -> Node<'t> {
+    if b.end_byte() - b.start_byte() > a.end_byte() - a.start_byte() {
+        b
+    } else {
+        a
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GO_QUERY: &str = r#"
+((comment)+ @doc
+ .
+ (function_declaration body: (block) @body) @function)
+
+(function_declaration body: (block) @body) @function
+
+((call_expression function: (identifier) @_f) @placeholder
+ (#eq? @_f "panic"))
+"#;
+
+    fn go_backend() -> QueryBackend {
+        QueryBackend::new(
+            tree_sitter_go::LANGUAGE.into(),
+            GO_QUERY,
+            QueryConfig::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_doc_and_placeholder_captures() {
+        let backend = go_backend();
+        let code = "package main\n\n// Sum adds\n// all values\nfunc Sum(xs []int) int {\n\tpanic(\"todo\")\n}\n";
+        let cursor_byte = code.find("panic").unwrap();
+
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.signature, "func Sum(xs []int) int");
+        assert_eq!(info.doc_comment, Some("Sum adds\nall values".to_string()));
+        assert_eq!(
+            &code[info.start_byte..info.end_byte],
+            "\n\tpanic(\"todo\")\n"
+        );
+    }
+
+    #[test]
+    fn test_innermost_function_wins() {
+        let backend = QueryBackend::new(
+            tree_sitter_javascript::LANGUAGE.into(),
+            "[(function_declaration body: (statement_block) @body)
+              (arrow_function body: (statement_block) @body)] @function",
+            QueryConfig::default(),
+        )
+        .unwrap();
+        let code = "function outer() { const f = () => {}; }";
+        let cursor_byte = code.find("{}").unwrap() + 1;
+
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+        assert_eq!(info.signature, "() =>");
+
+        // The outer function has code, so nothing is found around it
+        let result = backend.find_empty_function_at_cursor(code, 2);
+        assert!(result.is_none());
+    }
+
+    #[test]
+    fn test_rejects_query_without_required_captures() {
+        let result = QueryBackend::new(
+            tree_sitter_go::LANGUAGE.into(),
+            "(function_declaration) @function",
+            QueryConfig::default(),
+        );
+        assert!(result.is_err());
+
+        let result = QueryBackend::new(
+            tree_sitter_go::LANGUAGE.into(),
+            "(not_a_node) @function",
+            QueryConfig::default(),
+        );
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,197 @@
+use crate::languages::c_backend::CBackend;
+use crate::languages::cpp_backend::CppBackend;
+use crate::languages::config::LanguageConfig;
+use crate::languages::dynamic::DynamicBackend;
+use crate::languages::go_backend::GoBackend;
+use crate::languages::java_backend::JavaBackend;
+use crate::languages::js_backend::js_backend;
+use crate::languages::jsx_backend::jsx_backend;
+use crate::languages::kotlin_backend::KotlinBackend;
+use crate::languages::language_standard::LanguageStandard;
+use crate::languages::python_backend::PythonBackend;
+use crate::languages::rust_backend::rust_backend;
+use crate::languages::ts_backend::ts_backend;
+use crate::languages::tsx_backend::tsx_backend;
+use anyhow::{Result, bail};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Maps the `file_type` of a request to the backend handling it
+#[derive(Default)]
+pub struct LanguageRegistry {
+    backends: HashMap<String, Arc<dyn LanguageStandard>>,
+    /// Why the grammar of a file type failed to load
+    failures: HashMap<String, String>,
+    /// Backend of requests naming none, by file type
+    preferred_backends: HashMap<String, String>,
+}
+
+impl LanguageRegistry {
+    /// Registry with every language compiled into chace
+    pub fn with_builtin() -> Self {
+        let mut registry = LanguageRegistry::default();
+
+        registry.register(&["rust"], Arc::new(rust_backend()));
+        registry.register(&["ts", "typescript"], Arc::new(ts_backend()));
+        registry.register(&["tsx", "typescriptreact"], Arc::new(tsx_backend()));
+        registry.register(&["js", "javascript"], Arc::new(js_backend()));
+        registry.register(&["jsx", "javascriptreact"], Arc::new(jsx_backend()));
+        registry.register(&["py", "python"], Arc::new(PythonBackend));
+        registry.register(&["go", "golang"], Arc::new(GoBackend));
+        registry.register(&["c"], Arc::new(CBackend));
+        registry.register(&["cpp", "c++", "cc", "cxx"], Arc::new(CppBackend));
+        registry.register(&["java"], Arc::new(JavaBackend));
+        registry.register(&["kt", "kotlin"], Arc::new(KotlinBackend));
+
+        registry
+    }
+
+    /// Registers a backend under one or more file types, replacing any
+    /// backend previously registered for them
+    pub fn register(
+        &mut self,
+        file_types: &[&str],
+        backend: Arc<dyn LanguageStandard>,
+    ) {
+        for file_type in file_types {
+            self.backends.insert(file_type.to_string(), Arc::clone(&backend));
+        }
+    }
+
+    /// Applies the `[languages]` tables of the config file. A grammar that
+    /// fails to load is recorded against its file types instead of aborting.
+    pub fn apply_config(
+        &mut self,
+        languages: &BTreeMap<String, LanguageConfig>,
+    ) -> Result<()> {
+        for (name, config) in languages {
+            let mut file_types = vec![name.as_str()];
+            file_types.extend(config.file_types.iter().map(String::as_str));
+
+            match &config.grammar {
+                Some(grammar) => match DynamicBackend::load(name, grammar) {
+                    Ok(backend) => {
+                        self.register(&file_types, Arc::new(backend));
+                        for file_type in &file_types {
+                            self.failures.remove(*file_type);
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("failed to load {name} grammar: {e:#}");
+                        for file_type in &file_types {
+                            self.failures.insert(
+                                file_type.to_string(),
+                                format!("Failed to load {name} grammar: {e:#}"),
+                            );
+                        }
+                    }
+                },
+                None => {
+                    let Some(backend) = self.get(name) else {
+                        bail!(
+                            "languages.{name}: not a built-in language, add a \
+                             [languages.{name}.grammar] table to load it"
+                        );
+                    };
+                    self.register(&file_types, backend);
+                }
+            }
+
+            if let Some(backend) = &config.backend {
+                for file_type in &file_types {
+                    self.preferred_backends
+                        .insert(file_type.to_string(), backend.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, file_type: &str) -> Option<Arc<dyn LanguageStandard>> {
+        self.backends.get(file_type).cloned()
+    }
+
+    /// Backend configured for a file type
+    pub fn backend_for(&self, file_type: &str) -> Option<&str> {
+        self.preferred_backends.get(file_type).map(String::as_str)
+    }
+
+    /// Load error for a file type whose grammar is configured but unusable
+    pub fn load_error(&self, file_type: &str) -> Option<&str> {
+        self.failures.get(file_type).map(String::as_str)
+    }
+
+    /// File types requests may name, aliases included
+    pub fn file_types(&self) -> Vec<&str> {
+        let mut file_types: Vec<&str> =
+            self.backends.keys().map(String::as_str).collect();
+        file_types.sort();
+        file_types
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_languages_resolve() {
+        let registry = LanguageRegistry::with_builtin();
+
+        for file_type in [
+            "rust", "typescript", "tsx", "js", "jsx", "python", "go", "java",
+            "kotlin",
+        ] {
+            assert!(registry.get(file_type).is_some(), "{file_type} missing");
+        }
+        assert!(registry.get("cobol").is_none());
+    }
+
+    #[test]
+    fn test_grammar_failures_are_per_language() {
+        let mut registry = LanguageRegistry::with_builtin();
+        let languages: BTreeMap<String, LanguageConfig> = toml::from_str(
+            r#"
+[elixir]
+file_types = ["ex"]
+grammar = { library = "/nonexistent/elixir.so", query = "/nonexistent/elixir.scm" }
+"#,
+        )
+        .unwrap();
+
+        registry.apply_config(&languages).unwrap();
+
+        assert!(registry.get("ex").is_none());
+        assert!(registry.load_error("ex").unwrap().contains("elixir"));
+        assert!(registry.load_error("rust").is_none());
+        assert!(registry.get("rust").is_some());
+    }
+
+    #[test]
+    fn test_builtin_language_options() {
+        let mut registry = LanguageRegistry::with_builtin();
+        let languages: BTreeMap<String, LanguageConfig> = toml::from_str(
+            r#"
+[python]
+file_types = ["pyi"]
+backend = "local"
+"#,
+        )
+        .unwrap();
+
+        registry.apply_config(&languages).unwrap();
+
+        assert!(registry.get("pyi").is_some());
+        assert_eq!(registry.backend_for("pyi"), Some("local"));
+        assert_eq!(registry.backend_for("python"), Some("local"));
+        assert_eq!(registry.backend_for("rust"), None);
+
+        let unknown: BTreeMap<String, LanguageConfig> =
+            toml::from_str("[cobol]\nbackend = \"local\"\n").unwrap();
+        let err = registry.apply_config(&unknown).unwrap_err().to_string();
+        assert!(err.starts_with("languages.cobol: not a built-in language"));
+    }
+}
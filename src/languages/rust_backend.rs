@@ -1,101 +1,17 @@
-use crate::languages::helpers::{
-    extract_doc_comment, extract_signature, is_empty_body, text_for,
-};
-use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
-use tree_sitter::Parser;
+use crate::languages::query_backend::{QueryBackend, QueryConfig};
 use tree_sitter_rust;
 
-pub struct RustBackend;
-
-impl LanguageStandard for RustBackend {
-    fn find_empty_function_at_cursor(
-        &self,
-        source_code: &str,
-        cursor_byte: usize,
-    ) -> Option<FunctionInfo> {
-        let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_rust::LANGUAGE.into())
-            .expect("Failed to load Rust grammar");
-
-        let tree = parser.parse(source_code, None)?;
-        let root = tree.root_node();
-        let mut cursor = root.walk();
-
-        for node in root.children(&mut cursor) {
-            if node.kind() != "function_item" {
-                continue;
-            }
-
-            // Check if the cursor is inside the function
-            if cursor_byte < node.start_byte() || cursor_byte > node.end_byte()
-            {
-                continue;
-            }
-
-            // Check if empty
-            let body = match node.child_by_field_name("body") {
-                Some(body_node) => text_for(source_code, &body_node),
-                None => continue,
-            };
-            if !is_empty_body(body) {
-                continue;
-            }
-
-            let body_node = node.child_by_field_name("body").unwrap();
-
-            let signature = extract_signature(&node, source_code);
-            let doc_comment = extract_doc_comment(&node, source_code, "///");
-
-            return Some(FunctionInfo {
-                signature,
-                doc_comment,
-                start_byte: body_node.start_byte() + 1,
-                end_byte: body_node.end_byte() - 1,
-            });
-        }
-
-        None
-    }
-
-    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
-        let mut parser = Parser::new();
-
-        parser
-            .set_language(&tree_sitter_rust::LANGUAGE.into())
-            .expect("Error loading Rust grammar");
-
-        let tree = parser.parse(source_code, None).unwrap();
-        let root_node = tree.root_node();
-        let mut cursor = root_node.walk();
-        let mut funcs = Vec::new();
-
-        for node in root_node.children(&mut cursor) {
-            if node.kind() != "function_item" {
-                continue;
-            }
-
-            let body = match node.child_by_field_name("body") {
-                Some(body_node) => text_for(source_code, &body_node),
-                None => continue,
-            };
-            if !is_empty_body(body) {
-                continue;
-            }
-
-            let signature = extract_signature(&node, source_code);
-            let doc_comment = extract_doc_comment(&node, source_code, "///");
-
-            funcs.push(FunctionInfo {
-                signature,
-                doc_comment,
-                start_byte: node.start_byte(),
-                end_byte: node.end_byte(),
-            });
-        }
-
-        funcs
-    }
+/// Rust backend, driven by `queries/rust.scm`
+pub fn rust_backend() -> QueryBackend {
+    QueryBackend::new(
+        tree_sitter_rust::LANGUAGE.into(),
+        include_str!("../../queries/rust.scm"),
+        QueryConfig {
+            doc_prefixes: vec!["///".to_string()],
+            ..Default::default()
+        },
+    )
+    .expect("Invalid Rust query")
 }
 
 //-----------------------------Unit Tests--------------------------------------
@@ -103,10 +19,11 @@ impl LanguageStandard for RustBackend {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::languages::language_standard::LanguageStandard;
 
     #[test]
     fn test_find_simple_empty_function() {
-        let backend = RustBackend;
+        let backend = rust_backend();
         let code = r#"
 /// This is a test function
 fn hello_world(name: &str) -> String {
@@ -129,7 +46,7 @@ fn hello_world(name: &str) -> String {
 
     #[test]
     fn test_find_function_with_complex_signature() {
-        let backend = RustBackend;
+        let backend = rust_backend();
         let code = r#"
 /// Multi-line doc
 /// second line
@@ -147,7 +64,7 @@ where T: Into<String>
 
     #[test]
     fn test_ignores_populated_function() {
-        let backend = RustBackend;
+        let backend = rust_backend();
         let code = "fn has_code() { println!(\"hi\"); }";
         let cursor_byte = code.find('p').unwrap(); // Cursor on println
         
@@ -157,11 +74,132 @@ where T: Into<String>
 
     #[test]
     fn test_cursor_outside_function() {
-        let backend = RustBackend;
+        let backend = rust_backend();
         let code = "fn empty() {} \n // cursor is here";
         let cursor_byte = code.len() - 1;
         
         let result = backend.find_empty_function_at_cursor(code, cursor_byte);
         assert!(result.is_none(), "Should ignore if cursor is outside bounds");
     }
+
+    #[test]
+    fn test_find_method_in_impl_block() {
+        let backend = rust_backend();
+        let code = r#"
+struct Stack<T> { items: Vec<T> }
+
+impl<T: Clone> Stack<T> {
+    pub fn len(&self) -> usize { self.items.len() }
+
+    /// Returns the top item
+    pub fn peek(&self) -> Option<&T> {
+    }
+}"#;
+        let cursor_byte = code.rfind('}').unwrap() - 2;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(
+            info.signature,
+            "impl<T: Clone> Stack<T> {\n    pub fn peek(&self) -> Option<&T>"
+        );
+        assert_eq!(info.doc_comment, Some("Returns the top item".to_string()));
+
+        let body_content = &code[info.start_byte..info.end_byte];
+        assert!(body_content.trim().is_empty());
+    }
+
+    #[test]
+    fn test_find_trait_default_method() {
+        let backend = rust_backend();
+        let code = r#"
+trait Shape where Self: Sized {
+    fn area(&self) -> f64;
+    fn describe(&self) -> String {
+    }
+}"#;
+        let cursor_byte = code.find("String {").unwrap() + 8;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(
+            info.signature,
+            "trait Shape where Self: Sized {\n    fn describe(&self) -> String"
+        );
+    }
+
+    #[test]
+    fn test_find_function_in_module() {
+        let backend = rust_backend();
+        let code = r#"
+mod tests {
+    fn helper() -> u32 {
+    }
+
+    fn other() { helper(); }
+}"#;
+        let cursor_byte = code.find("u32 {").unwrap() + 5;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(info.signature, "fn helper() -> u32");
+
+        let funcs = backend.find_empty_functions(code);
+        assert_eq!(funcs.len(), 1);
+        assert_eq!(funcs[0].start_byte, info.start_byte);
+    }
+
+    #[test]
+    fn test_placeholder_bodies_count_as_empty() {
+        let backend = rust_backend();
+        let code = r#"
+fn a() -> u32 { todo!() }
+fn b() -> u32 { std::unimplemented!("later"); }
+fn c() -> u32 { panic!("not implemented") }
+fn d() -> u32 {
+    // TODO
+}
+fn e() -> u32 { panic!("invalid state") }
+fn f() -> u32 { todo!(); 1 }
+"#;
+
+        let funcs = backend.find_empty_functions(code);
+        let names: Vec<_> =
+            funcs.iter().map(|f| f.signature.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["fn a() -> u32", "fn b() -> u32", "fn c() -> u32", "fn d() -> u32"]
+        );
+
+        // The whole placeholder is replaced
+        assert_eq!(&code[funcs[0].start_byte..funcs[0].end_byte], " todo!() ");
+    }
+
+    #[test]
+    fn test_comment_only_body_becomes_instructions() {
+        let backend = rust_backend();
+        let code = r#"
+/// Finds the index of the target
+fn search(items: &[i32], target: i32) -> Option<usize> {
+    // use binary search
+    // return None on miss
+}"#;
+        let cursor_byte = code.find("use binary").unwrap();
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(
+            info.instructions,
+            Some("use binary search\nreturn None on miss".to_string())
+        );
+        assert_eq!(
+            info.doc_comment,
+            Some("Finds the index of the target".to_string())
+        );
+
+        // A bare TODO marker carries no guidance
+        let code = "fn later() -> u32 {\n    /* TODO */\n    todo!()\n}";
+        let info = backend.find_empty_function_at_cursor(code, 20).unwrap();
+        assert_eq!(info.instructions, None);
+    }
 }
@@ -1,102 +1,18 @@
-use crate::languages::helpers::{
-    extract_doc_comment, extract_signature, is_empty_body, text_for,
-};
-use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
-use tree_sitter::Parser;
+use crate::languages::query_backend::{QueryBackend, QueryConfig};
 use tree_sitter_typescript;
 
-pub struct TsBackend;
-
-impl LanguageStandard for TsBackend {
-    fn find_empty_function_at_cursor(
-        &self,
-        source_code: &str,
-        cursor_byte: usize,
-    ) -> Option<FunctionInfo> {
-        let mut parser = Parser::new();
-        parser
-            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
-            .expect("Failed to load Typescript grammar");
-
-        let tree = parser.parse(source_code, None)?;
-        let root = tree.root_node();
-        let mut cursor = root.walk();
-
-        for node in root.children(&mut cursor) {
-            let kind = node.kind();
-            if kind != "function_declaration" && kind != "method_definition" {
-                continue;
-            }
-
-            // Check if the cursor is inside the function
-            if cursor_byte < node.start_byte() || cursor_byte > node.end_byte()
-            {
-                continue;
-            }
-
-            // Check if empty
-            let body = match node.child_by_field_name("body") {
-                Some(body_node) => text_for(source_code, &body_node),
-                None => continue,
-            };
-            if !is_empty_body(body) {
-                continue;
-            }
-
-            let body_node = node.child_by_field_name("body").unwrap();
-
-            let signature = extract_signature(&node, source_code);
-            let doc_comment = extract_doc_comment(&node, source_code, "/**");
-
-            return Some(FunctionInfo {
-                signature,
-                doc_comment,
-                start_byte: body_node.start_byte() + 1,
-                end_byte: body_node.end_byte() - 1,
-            });
-        }
-
-        None
-    }
-
-    fn find_empty_functions(&self, source_code: &str) -> Vec<FunctionInfo> {
-        let mut parser = Parser::new();
-
-        parser
-            .set_language(&tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into())
-            .expect("Error loading Typescript grammar");
-
-        let tree = parser.parse(source_code, None).unwrap();
-        let root_node = tree.root_node();
-        let mut cursor = root_node.walk();
-        let mut funcs = Vec::new();
-
-        for node in root_node.children(&mut cursor) {
-            if node.kind() != "function_item" {
-                continue;
-            }
-
-            let body = match node.child_by_field_name("body") {
-                Some(body_node) => text_for(source_code, &body_node),
-                None => continue,
-            };
-            if !is_empty_body(body) {
-                continue;
-            }
-
-            let signature = extract_signature(&node, source_code);
-            let doc_comment = extract_doc_comment(&node, source_code, "/**");
-
-            funcs.push(FunctionInfo {
-                signature,
-                doc_comment,
-                start_byte: node.start_byte(),
-                end_byte: node.end_byte(),
-            });
-        }
-
-        funcs
-    }
+/// TypeScript backend, driven by `queries/typescript.scm`
+pub fn ts_backend() -> QueryBackend {
+    QueryBackend::new(
+        tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+        include_str!("../../queries/typescript.scm"),
+        QueryConfig {
+            doc_prefixes: vec!["/**".to_string()],
+            field_kinds: vec!["public_field_definition".to_string()],
+            field_terminator: Some(";".to_string()),
+        },
+    )
+    .expect("Invalid TypeScript query")
 }
 
 //-----------------------------Unit Tests--------------------------------------
@@ -104,10 +20,11 @@ impl LanguageStandard for TsBackend {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::languages::language_standard::LanguageStandard;
 
     #[test]
     fn test_find_simple_empty_function() {
-        let backend = TsBackend;
+        let backend = ts_backend();
         let code = r#"
 /**
  * This is a test function
@@ -130,7 +47,7 @@ function helloWorld(name: string): string {
 
     #[test]
     fn test_find_function_with_complex_signature() {
-        let backend = TsBackend;
+        let backend = ts_backend();
         let code = r#"
 /**
  * Multi-line doc
@@ -147,7 +64,7 @@ async function fetchData<T>(url: T): Promise<Uint8Array> {
 
     #[test]
     fn test_ignores_populated_function() {
-        let backend = TsBackend;
+        let backend = ts_backend();
         let code = r#"function hasCode() { console.log("hi"); }"#;
         let cursor_byte = code.find('c').unwrap();
         
@@ -157,11 +74,39 @@ async function fetchData<T>(url: T): Promise<Uint8Array> {
 
     #[test]
     fn test_cursor_outside_function() {
-        let backend = TsBackend;
+        let backend = ts_backend();
         let code = "function empty() {} \n // cursor is here";
         let cursor_byte = code.len() - 1;
         
         let result = backend.find_empty_function_at_cursor(code, cursor_byte);
         assert!(result.is_none(), "Should ignore if cursor is outside bounds");
     }
+
+    #[test]
+    fn test_find_class_method_with_fields() {
+        let backend = ts_backend();
+        let code = r#"
+class Counter extends Base {
+    private count: number = 0;
+    readonly step = 1
+
+    /**
+     * Increments the counter
+     */
+    increment(by: number): number {
+    }
+}"#;
+        let cursor_byte = code.find("number {").unwrap() + 8;
+        let info =
+            backend.find_empty_function_at_cursor(code, cursor_byte).unwrap();
+
+        assert_eq!(
+            info.signature,
+            "class Counter extends Base {\n    \
+             private count: number = 0;\n    \
+             readonly step = 1;\n\n    \
+             increment(by: number): number"
+        );
+        assert_eq!(info.doc_comment, Some("Increments the counter".to_string()));
+    }
 }
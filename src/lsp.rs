@@ -0,0 +1,1039 @@
+use crate::ai::backend::GenerationOptions;
+use crate::rpc::{INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND};
+use crate::rpc::{PARSE_ERROR, REQUEST_CANCELLED};
+use crate::pending::{Finished, PendingRequests};
+use crate::request::{
+    ChunkSink, GenerateRequest, RequestId, handle_request, send,
+};
+use crate::{Registries, rpc};
+use anyhow::{Result, bail};
+use arc_swap::ArcSwap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncReadExt};
+use tokio::io::{AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+
+/// Title of the code action offered on empty functions
+const ACTION_TITLE: &str = "Implement function with AI";
+
+/// Command run by clients that cannot resolve code actions lazily
+const IMPLEMENT_COMMAND: &str = "chace.implementFunction";
+
+/// The document changed since the code action was offered
+const CONTENT_MODIFIED: i64 = -32801;
+/// The request was valid but could not be served, e.g. a generation failed
+const REQUEST_FAILED: i64 = -32803;
+
+/// Unit `character` offsets of positions are counted in
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Utf8,
+    /// The default of LSP
+    Utf16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+struct Position {
+    line: u32,
+    character: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+struct Range {
+    start: Position,
+    end: Position,
+}
+
+/// An open document, kept in sync through `didOpen` and `didChange`
+#[derive(Clone)]
+struct Document {
+    language_id: String,
+    version: i32,
+    text: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TextDocumentItem {
+    uri: String,
+    language_id: String,
+    version: i32,
+    text: String,
+}
+
+#[derive(Deserialize)]
+struct TextDocumentIdentifier {
+    uri: String,
+}
+
+#[derive(Deserialize)]
+struct VersionedTextDocumentIdentifier {
+    uri: String,
+    version: i32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidOpenParams {
+    text_document: TextDocumentItem,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidChangeParams {
+    text_document: VersionedTextDocumentIdentifier,
+    /// With full sync, the last change holds the whole text
+    content_changes: Vec<ContentChange>,
+}
+
+#[derive(Deserialize)]
+struct ContentChange {
+    text: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DidCloseParams {
+    text_document: TextDocumentIdentifier,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CodeActionParams {
+    text_document: TextDocumentIdentifier,
+    range: Range,
+}
+
+#[derive(Deserialize)]
+struct ExecuteCommandParams {
+    command: String,
+    #[serde(default)]
+    arguments: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct CancelParams {
+    id: RequestId,
+}
+
+/// What a code action needs to be carried out later, by `codeAction/resolve`
+/// or [`IMPLEMENT_COMMAND`]
+#[derive(Serialize, Deserialize)]
+struct ActionData {
+    uri: String,
+    /// Version the action was offered for, stale actions are refused
+    version: i32,
+    cursor_byte: usize,
+}
+
+/// A message of either side, only the fields read here
+#[derive(Deserialize)]
+struct Message {
+    #[serde(default)]
+    id: Option<RequestId>,
+    /// Missing for responses to requests of the server
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    params: Value,
+}
+
+/// Serves LSP over `input` and `output` until the client sends `exit`. Code
+/// actions implementing empty functions are the only feature. Returns the
+/// exit code LSP asks for: 0 after a `shutdown`, 1 otherwise.
+pub async fn serve(
+    input: impl AsyncRead + Unpin + Send + 'static,
+    mut output: impl AsyncWrite + Unpin + Send + 'static,
+    registries: Arc<ArcSwap<Registries>>,
+) -> Result<i32> {
+    // messages are read on their own task, reads are not cancel safe
+    let (incoming_tx, mut incoming) = mpsc::unbounded_channel::<Vec<u8>>();
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(input);
+        loop {
+            match read_message(&mut reader).await {
+                Ok(Some(body)) => {
+                    if incoming_tx.send(body).is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    eprintln!("lsp: {e:#}");
+                    break;
+                }
+            }
+        }
+    });
+
+    let (out, mut outgoing) = mpsc::unbounded_channel::<String>();
+    let writer_task = tokio::spawn(async move {
+        while let Some(message) = outgoing.recv().await {
+            let header = format!("Content-Length: {}\r\n\r\n", message.len());
+            output.write_all(header.as_bytes()).await?;
+            output.write_all(message.as_bytes()).await?;
+            output.flush().await?;
+        }
+        Ok::<_, std::io::Error>(())
+    });
+
+    let mut server = Server {
+        registries,
+        out,
+        documents: HashMap::new(),
+        encoding: Encoding::Utf16,
+        resolve_edits: false,
+        versioned_edits: false,
+        running: PendingRequests::new(),
+        next_id: 0,
+        shut_down: false,
+    };
+
+    loop {
+        tokio::select! {
+            body = incoming.recv() => {
+                // the client went away without `exit`
+                let Some(body) = body else { break };
+                if !server.handle(&body) {
+                    break;
+                }
+            }
+            finished = server.running.next(), if !server.running.is_empty() => {
+                match finished {
+                    Some(Finished::Answered(message)) => {
+                        let _ = server.out.send(message);
+                    }
+                    Some(Finished::Failed(id, ())) => {
+                        let message = "Request failed";
+                        let reply =
+                            rpc::error(id.as_ref(), REQUEST_FAILED, message);
+                        let _ = server.out.send(reply);
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
+    let shut_down = server.shut_down;
+    drop(server);
+    writer_task.await??;
+    Ok(if shut_down { 0 } else { 1 })
+}
+
+struct Server {
+    registries: Arc<ArcSwap<Registries>>,
+    out: mpsc::UnboundedSender<String>,
+    documents: HashMap<String, Document>,
+    encoding: Encoding,
+    /// Whether the client asks for the edit of a code action once chosen,
+    /// instead of running its command
+    resolve_edits: bool,
+    /// Whether the client takes edits tied to a document version, so edits
+    /// made for an older text are refused rather than misplaced
+    versioned_edits: bool,
+    /// Generations, answered as they complete
+    running: PendingRequests<()>,
+    /// Id of the next request sent to the client
+    next_id: i64,
+    shut_down: bool,
+}
+
+impl Server {
+    /// Handles a message of the client, returning false on `exit`
+    fn handle(&mut self, body: &[u8]) -> bool {
+        let message = match serde_json::from_slice::<Message>(body) {
+            Ok(message) => message,
+            Err(e) => {
+                let message = format!("Parse error: {e}");
+                let _ = self.out.send(rpc::error(None, PARSE_ERROR, &message));
+                return true;
+            }
+        };
+
+        match (message.id, message.method) {
+            (Some(id), Some(method)) => {
+                self.request(id, &method, message.params);
+                true
+            }
+            (None, Some(method)) => self.notify(&method, message.params),
+            // answers to `workspace/applyEdit`, nothing to do with them
+            (_, None) => true,
+        }
+    }
+
+    fn request(&mut self, id: RequestId, method: &str, params: Value) {
+        if self.shut_down {
+            let message = "Server is shut down";
+            let reply = rpc::error(Some(&id), INVALID_REQUEST, message);
+            let _ = self.out.send(reply);
+            return;
+        }
+        // responses are told apart by id, which must be unique
+        if self.running.contains(&id) {
+            let message = "Duplicate request id";
+            let reply = rpc::error(Some(&id), INVALID_REQUEST, message);
+            let _ = self.out.send(reply);
+            return;
+        }
+
+        let reply = match method {
+            "initialize" => rpc::result(Some(&id), self.initialize(&params)),
+            "shutdown" => {
+                self.shut_down = true;
+                self.cancel_all();
+                rpc::result(Some(&id), Value::Null)
+            }
+            "textDocument/codeAction" => {
+                match CodeActionParams::deserialize(params) {
+                    Ok(params) => {
+                        rpc::result(Some(&id), self.code_actions(&params))
+                    }
+                    Err(e) => invalid_params(&id, e),
+                }
+            }
+            "codeAction/resolve" => return self.resolve(id, params),
+            "workspace/executeCommand" => {
+                match ExecuteCommandParams::deserialize(params) {
+                    Ok(params) => return self.execute(id, params),
+                    Err(e) => invalid_params(&id, e),
+                }
+            }
+            other => {
+                let message = format!("Method not found: {other}");
+                rpc::error(Some(&id), METHOD_NOT_FOUND, &message)
+            }
+        };
+        let _ = self.out.send(reply);
+    }
+
+    /// Handles a notification, returning false on `exit`. Invalid ones are
+    /// dropped, notifications are never answered.
+    fn notify(&mut self, method: &str, params: Value) -> bool {
+        match method {
+            "exit" => return false,
+            "textDocument/didOpen" => {
+                if let Ok(params) = DidOpenParams::deserialize(params) {
+                    let doc = params.text_document;
+                    self.documents.insert(doc.uri, Document {
+                        language_id: doc.language_id,
+                        version: doc.version,
+                        text: doc.text,
+                    });
+                }
+            }
+            "textDocument/didChange" => {
+                if let Ok(mut params) = DidChangeParams::deserialize(params)
+                    && let Some(doc) =
+                        self.documents.get_mut(&params.text_document.uri)
+                    && let Some(change) = params.content_changes.pop()
+                {
+                    doc.version = params.text_document.version;
+                    doc.text = change.text;
+                }
+            }
+            "textDocument/didClose" => {
+                if let Ok(params) = DidCloseParams::deserialize(params) {
+                    self.documents.remove(&params.text_document.uri);
+                }
+            }
+            "$/cancelRequest" => {
+                if let Ok(params) = CancelParams::deserialize(params)
+                    && self.running.cancel(&params.id).is_some()
+                {
+                    let reply = rpc::error(
+                        Some(&params.id),
+                        REQUEST_CANCELLED,
+                        "cancelled",
+                    );
+                    let _ = self.out.send(reply);
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        let capabilities = &params["capabilities"];
+        let offers = |pointer: &str, value: &str| {
+            capabilities
+                .pointer(pointer)
+                .and_then(Value::as_array)
+                .is_some_and(|values| values.contains(&value.into()))
+        };
+
+        // UTF-8 spares converting offsets, but only if the client knows it
+        if offers("/general/positionEncodings", "utf-8") {
+            self.encoding = Encoding::Utf8;
+        }
+        let resolvable = "/textDocument/codeAction/resolveSupport/properties";
+        self.resolve_edits = offers(resolvable, "edit");
+        self.versioned_edits = capabilities
+            .pointer("/workspace/workspaceEdit/documentChanges")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+
+        json!({
+            "capabilities": {
+                "positionEncoding": match self.encoding {
+                    Encoding::Utf8 => "utf-8",
+                    Encoding::Utf16 => "utf-16",
+                },
+                // full sync: every change sends the whole text
+                "textDocumentSync": { "openClose": true, "change": 1 },
+                "codeActionProvider": {
+                    "codeActionKinds": ["refactor.rewrite"],
+                    "resolveProvider": true,
+                },
+                "executeCommandProvider": { "commands": [IMPLEMENT_COMMAND] },
+            },
+            "serverInfo": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    /// Offers the code action when the range starts in an empty function.
+    /// Nothing is generated until the action is chosen.
+    fn code_actions(&self, params: &CodeActionParams) -> Value {
+        let uri = &params.text_document.uri;
+        let Some(doc) = self.documents.get(uri) else {
+            return json!([]);
+        };
+        let registries = self.registries.load();
+        let Some(language) = registries.languages.get(&doc.language_id) else {
+            return json!([]);
+        };
+
+        let cursor_byte = offset(&doc.text, params.range.start, self.encoding);
+        if language
+            .find_empty_function_at_cursor(&doc.text, cursor_byte)
+            .is_none()
+        {
+            return json!([]);
+        }
+
+        let data = ActionData {
+            uri: uri.clone(),
+            version: doc.version,
+            cursor_byte,
+        };
+        if self.resolve_edits {
+            json!([{
+                "title": ACTION_TITLE,
+                "kind": "refactor.rewrite",
+                "data": data,
+            }])
+        } else {
+            json!([{
+                "title": ACTION_TITLE,
+                "kind": "refactor.rewrite",
+                "command": {
+                    "title": ACTION_TITLE,
+                    "command": IMPLEMENT_COMMAND,
+                    "arguments": [data],
+                },
+            }])
+        }
+    }
+
+    /// Fills in the edit of a chosen code action
+    fn resolve(&mut self, id: RequestId, mut action: Value) {
+        let data = match ActionData::deserialize(&action["data"]) {
+            Ok(data) => data,
+            Err(e) => {
+                let _ = self.out.send(invalid_params(&id, e));
+                return;
+            }
+        };
+        let doc = match self.document(&data) {
+            Ok(doc) => doc,
+            Err((code, message)) => {
+                let _ = self.out.send(rpc::error(Some(&id), code, message));
+                return;
+            }
+        };
+
+        let registries = self.registries.load_full();
+        let (encoding, versioned) = (self.encoding, self.versioned_edits);
+        let reply_to = id.clone();
+        self.running.spawn(Some(id), (), async move {
+            match implement(&data, doc, &registries, encoding, versioned).await
+            {
+                Ok(edit) => {
+                    action["edit"] = edit;
+                    rpc::result(Some(&reply_to), action)
+                }
+                Err(message) => {
+                    rpc::error(Some(&reply_to), REQUEST_FAILED, &message)
+                }
+            }
+        });
+    }
+
+    /// Runs [`IMPLEMENT_COMMAND`], asking the client to apply the edit
+    fn execute(&mut self, id: RequestId, params: ExecuteCommandParams) {
+        let data = match params.arguments.first().map(ActionData::deserialize)
+        {
+            Some(Ok(data)) if params.command == IMPLEMENT_COMMAND => data,
+            _ => {
+                let message = format!("Unknown command: {}", params.command);
+                let reply = rpc::error(Some(&id), INVALID_PARAMS, &message);
+                let _ = self.out.send(reply);
+                return;
+            }
+        };
+        let doc = match self.document(&data) {
+            Ok(doc) => doc,
+            Err((code, message)) => {
+                let _ = self.out.send(rpc::error(Some(&id), code, message));
+                return;
+            }
+        };
+
+        let registries = self.registries.load_full();
+        let (encoding, versioned) = (self.encoding, self.versioned_edits);
+        let out = self.out.clone();
+        let edit_id = self.next_id;
+        self.next_id += 1;
+        let reply_to = id.clone();
+        self.running.spawn(Some(id), (), async move {
+            match implement(&data, doc, &registries, encoding, versioned).await
+            {
+                Ok(edit) => {
+                    send(&out, &json!({
+                        "jsonrpc": "2.0",
+                        "id": edit_id,
+                        "method": "workspace/applyEdit",
+                        "params": { "label": ACTION_TITLE, "edit": edit },
+                    }));
+                    rpc::result(Some(&reply_to), Value::Null)
+                }
+                Err(message) => {
+                    rpc::error(Some(&reply_to), REQUEST_FAILED, &message)
+                }
+            }
+        });
+    }
+
+    /// The document an action was offered for, as it was then
+    fn document(
+        &self,
+        data: &ActionData,
+    ) -> std::result::Result<Document, (i64, &'static str)> {
+        match self.documents.get(&data.uri) {
+            Some(doc) if doc.version == data.version => Ok(doc.clone()),
+            Some(_) => Err((CONTENT_MODIFIED, "Document changed, try again")),
+            None => Err((REQUEST_FAILED, "Document is not open")),
+        }
+    }
+
+    /// Drops every generation, so no edit is sent after `shutdown`
+    fn cancel_all(&mut self) {
+        for (id, ()) in self.running.cancel_all() {
+            let reply = rpc::error(Some(&id), REQUEST_CANCELLED, "cancelled");
+            let _ = self.out.send(reply);
+        }
+    }
+}
+
+fn invalid_params(id: &RequestId, e: serde_json::Error) -> String {
+    let message = format!("Invalid params: {e}");
+    rpc::error(Some(id), INVALID_PARAMS, &message)
+}
+
+/// Generates the body of the function at the cursor, as a `WorkspaceEdit`
+/// replacing the body range. Versioned edits only apply to the text the
+/// action was offered for, however the document changed meanwhile.
+async fn implement(
+    data: &ActionData,
+    doc: Document,
+    registries: &Registries,
+    encoding: Encoding,
+    versioned: bool,
+) -> std::result::Result<Value, String> {
+    let req = GenerateRequest {
+        id: None,
+        source_code: doc.text,
+        cursor_byte: data.cursor_byte,
+        backend: None,
+        file_type: doc.language_id,
+        context_snippets: None,
+        stream: false,
+        options: GenerationOptions::default(),
+    };
+    // the text is kept to place the edit, the request gives it away
+    let text = req.source_code.clone();
+    let chunks: ChunkSink = Arc::new(|_| {});
+
+    let Registries { backends, languages, .. } = registries;
+    let resp = handle_request(req, backends, languages, &chunks).await;
+    if let Some(e) = resp.error {
+        return Err(e);
+    }
+
+    let range = Range {
+        start: position(&text, resp.start_byte, encoding),
+        end: position(&text, resp.end_byte, encoding),
+    };
+    let edits = json!([{ "range": range, "newText": resp.body }]);
+    if !versioned {
+        return Ok(json!({ "changes": { &data.uri: edits } }));
+    }
+    Ok(json!({
+        "documentChanges": [{
+            "textDocument": { "uri": data.uri, "version": data.version },
+            "edits": edits,
+        }],
+    }))
+}
+
+/// Reads the body of a message framed by a `Content-Length` header, or
+/// `None` once the input ends
+async fn read_message(
+    reader: &mut (impl AsyncBufRead + Unpin),
+) -> Result<Option<Vec<u8>>> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.eq_ignore_ascii_case("content-length")
+        {
+            length = Some(value.trim().parse::<usize>()?);
+        }
+    }
+
+    let Some(length) = length else {
+        bail!("message without Content-Length");
+    };
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await?;
+    Ok(Some(body))
+}
+
+/// Byte offset of a position, clamped to the end of its line
+fn offset(text: &str, position: Position, encoding: Encoding) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match text[line_start..].find('\n') {
+            Some(end) => line_start += end + 1,
+            None => return text.len(),
+        }
+    }
+
+    let line = &text[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + i;
+        }
+        units += match encoding {
+            Encoding::Utf8 => c.len_utf8(),
+            Encoding::Utf16 => c.len_utf16(),
+        };
+    }
+    line_start + line.len()
+}
+
+/// Position of a byte offset, which must fall on a char boundary
+fn position(text: &str, offset: usize, encoding: Encoding) -> Position {
+    let before = &text[..offset];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let character = match encoding {
+        Encoding::Utf8 => offset - line_start,
+        Encoding::Utf16 => before[line_start..].encode_utf16().count(),
+    };
+    Position {
+        line: before.matches('\n').count() as u32,
+        character: character as u32,
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::backend::{LLMBackend, LLMResponse};
+    use crate::ai::registry::BackendRegistry;
+    use crate::languages::registry::LanguageRegistry;
+    use async_trait::async_trait;
+    use tokio::io::DuplexStream;
+    use tokio::sync::Notify;
+
+    /// A backend answering every request with the same body
+    struct Fixed;
+
+    #[async_trait]
+    impl LLMBackend for Fixed {
+        async fn generate_function(
+            &self,
+            _signature: &str,
+            _doc_comment: Option<&str>,
+            _instructions: Option<&str>,
+            _context_snippets: Option<&[String]>,
+            _language: &str,
+            _options: &GenerationOptions,
+        ) -> anyhow::Result<LLMResponse> {
+            Ok(LLMResponse {
+                body: "\n    a + b\n".into(),
+                usage: None,
+            })
+        }
+    }
+
+    /// A backend answering like [`Fixed`] once the gate is opened
+    struct Gated(Arc<Notify>);
+
+    #[async_trait]
+    impl LLMBackend for Gated {
+        async fn generate_function(
+            &self,
+            signature: &str,
+            doc_comment: Option<&str>,
+            instructions: Option<&str>,
+            context_snippets: Option<&[String]>,
+            language: &str,
+            options: &GenerationOptions,
+        ) -> anyhow::Result<LLMResponse> {
+            self.0.notified().await;
+            Fixed
+                .generate_function(
+                    signature,
+                    doc_comment,
+                    instructions,
+                    context_snippets,
+                    language,
+                    options,
+                )
+                .await
+        }
+    }
+
+    type Client = (BufReader<DuplexStream>, DuplexStream);
+
+    fn start() -> Client {
+        start_with(Arc::new(Fixed)).0
+    }
+
+    /// Serves with `backend` as default, returning the client and the
+    /// server's exit code to come
+    fn start_with(
+        backend: Arc<dyn LLMBackend>,
+    ) -> (Client, tokio::task::JoinHandle<Result<i32>>) {
+        let mut backends = BackendRegistry::default();
+        backends.register("fixed", backend);
+        backends.set_default(Some("fixed".into()));
+        let registries = Arc::new(ArcSwap::from_pointee(Registries {
+            backends,
+            languages: LanguageRegistry::with_builtin(),
+            max_concurrent_requests: 2,
+        }));
+
+        let (client_out, server_in) = tokio::io::duplex(4096);
+        let (server_out, client_in) = tokio::io::duplex(4096);
+        let server = tokio::spawn(serve(server_in, server_out, registries));
+        ((BufReader::new(client_in), client_out), server)
+    }
+
+    async fn send_message(client: &mut Client, message: Value) {
+        let body = message.to_string();
+        let frame = format!("Content-Length: {}\r\n\r\n{body}", body.len());
+        client.1.write_all(frame.as_bytes()).await.unwrap();
+    }
+
+    async fn receive(client: &mut Client) -> Value {
+        let body = read_message(&mut client.0).await.unwrap().unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    /// Initializes, then opens `text` as document `file:///a.rs`
+    async fn open(client: &mut Client, resolve_support: bool, text: &str) {
+        let properties = if resolve_support { vec!["edit"] } else { vec![] };
+        send_message(client, json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": { "capabilities": {
+                "textDocument": { "codeAction": {
+                    "resolveSupport": { "properties": properties },
+                }},
+                "workspace": { "workspaceEdit": { "documentChanges": true } },
+            }},
+        }))
+        .await;
+        let resp = receive(client).await;
+        let capabilities = &resp["result"]["capabilities"];
+        assert_eq!(capabilities["positionEncoding"], "utf-16");
+
+        send_message(client, json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": {
+                "uri": "file:///a.rs",
+                "languageId": "rust",
+                "version": 1,
+                "text": text,
+            }},
+        }))
+        .await;
+    }
+
+    fn code_action(id: u32, line: u32, character: u32) -> Value {
+        let position = json!({ "line": line, "character": character });
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": "textDocument/codeAction",
+            "params": {
+                "textDocument": { "uri": "file:///a.rs" },
+                "range": { "start": position, "end": position },
+                "context": { "diagnostics": [] },
+            },
+        })
+    }
+
+    #[test]
+    fn test_positions_in_both_encodings() {
+        let text = "// é😀\nfn f() {}\n";
+        let emoji_end = "// é😀".len();
+
+        let pos = position(text, emoji_end, Encoding::Utf16);
+        assert_eq!(pos, Position { line: 0, character: 6 });
+        assert_eq!(offset(text, pos, Encoding::Utf16), emoji_end);
+
+        let pos = position(text, emoji_end, Encoding::Utf8);
+        assert_eq!(pos, Position { line: 0, character: 9 });
+        assert_eq!(offset(text, pos, Encoding::Utf8), emoji_end);
+
+        let body = text.find('{').unwrap();
+        let pos = Position { line: 1, character: 7 };
+        assert_eq!(offset(text, pos, Encoding::Utf16), body);
+        // past the end of the line or of the text
+        let pos = Position { line: 1, character: 80 };
+        assert_eq!(offset(text, pos, Encoding::Utf16), text.len() - 1);
+        let pos = Position { line: 9, character: 0 };
+        assert_eq!(offset(text, pos, Encoding::Utf16), text.len());
+    }
+
+    #[tokio::test]
+    async fn test_read_framed_messages() {
+        let input = b"Content-Length: 2\r\nContent-Type: x\r\n\r\n{}\
+            content-length:4\r\n\r\nnull";
+        let mut reader = &input[..];
+        assert_eq!(read_message(&mut reader).await.unwrap().unwrap(), b"{}");
+        assert_eq!(read_message(&mut reader).await.unwrap().unwrap(), b"null");
+        assert!(read_message(&mut reader).await.unwrap().is_none());
+
+        let mut reader = &b"Content-Type: x\r\n\r\n"[..];
+        assert!(read_message(&mut reader).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_code_action_resolves_to_an_edit() {
+        let mut client = start();
+        open(&mut client, true, "fn add(a: i32, b: i32) -> i32 {}\n").await;
+
+        // only empty functions get the action
+        send_message(&mut client, code_action(2, 3, 0)).await;
+        assert_eq!(receive(&mut client).await["result"], json!([]));
+
+        send_message(&mut client, code_action(3, 0, 31)).await;
+        let resp = receive(&mut client).await;
+        let action = &resp["result"][0];
+        assert_eq!(action["title"], ACTION_TITLE);
+        assert!(action.get("edit").is_none());
+
+        send_message(&mut client, json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "codeAction/resolve",
+            "params": action,
+        }))
+        .await;
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 4);
+        let change = &resp["result"]["edit"]["documentChanges"][0];
+        assert_eq!(change["textDocument"]["version"], 1);
+        let edit = &change["edits"][0];
+        assert_eq!(edit["newText"], "\n    a + b\n");
+        assert_eq!(edit["range"]["start"], json!({"line": 0, "character": 31}));
+        assert_eq!(edit["range"]["end"], json!({"line": 0, "character": 31}));
+    }
+
+    #[tokio::test]
+    async fn test_stale_actions_and_command_fallback() {
+        let mut client = start();
+        open(&mut client, false, "fn f() {}\n").await;
+
+        send_message(&mut client, code_action(2, 0, 8)).await;
+        let resp = receive(&mut client).await;
+        let command = resp["result"][0]["command"].clone();
+        assert_eq!(command["command"], IMPLEMENT_COMMAND);
+
+        send_message(&mut client, json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": "file:///a.rs", "version": 2 },
+                "contentChanges": [{ "text": "fn g() {}\n" }],
+            },
+        }))
+        .await;
+        let execute = json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "workspace/executeCommand",
+            "params": command,
+        });
+        send_message(&mut client, execute.clone()).await;
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["error"]["code"], CONTENT_MODIFIED);
+
+        send_message(&mut client, code_action(4, 0, 8)).await;
+        let resp = receive(&mut client).await;
+        let mut execute = execute;
+        execute["params"] = resp["result"][0]["command"].clone();
+        send_message(&mut client, execute).await;
+
+        let edit = receive(&mut client).await;
+        assert_eq!(edit["method"], "workspace/applyEdit");
+        let change = &edit["params"]["edit"]["documentChanges"][0];
+        assert_eq!(change["textDocument"]["uri"], "file:///a.rs");
+        assert_eq!(change["textDocument"]["version"], 2);
+        assert_eq!(change["edits"][0]["newText"], "\n    a + b\n");
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 3);
+        assert_eq!(resp["result"], Value::Null);
+
+        send_message(&mut client, json!({
+            "jsonrpc": "2.0", "id": 5, "method": "shutdown",
+        }))
+        .await;
+        assert_eq!(receive(&mut client).await["result"], Value::Null);
+        send_message(&mut client, json!({"jsonrpc": "2.0", "method": "exit"}))
+            .await;
+        assert!(read_message(&mut client.0).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_edit_keeps_the_version_it_was_made_for() {
+        let gate = Arc::new(Notify::new());
+        let (mut client, _) = start_with(Arc::new(Gated(Arc::clone(&gate))));
+        open(&mut client, false, "fn f() {}\n").await;
+
+        send_message(&mut client, code_action(2, 0, 8)).await;
+        let resp = receive(&mut client).await;
+        send_message(&mut client, json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "workspace/executeCommand",
+            "params": resp["result"][0]["command"],
+        }))
+        .await;
+
+        // the text moves on while the body is being generated
+        send_message(&mut client, json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": "file:///a.rs", "version": 2 },
+                "contentChanges": [{ "text": "\n\nfn f() {}\n" }],
+            },
+        }))
+        .await;
+        gate.notify_one();
+
+        // so the client refuses the edit instead of misplacing it
+        let edit = receive(&mut client).await;
+        assert_eq!(edit["method"], "workspace/applyEdit");
+        let change = &edit["params"]["edit"]["documentChanges"][0];
+        assert_eq!(change["textDocument"]["version"], 1);
+        let range = &change["edits"][0]["range"];
+        assert_eq!(range["start"], json!({"line": 0, "character": 8}));
+        assert_eq!(receive(&mut client).await["id"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_duplicate_request_id() {
+        let gate = Arc::new(Notify::new());
+        let (mut client, _) = start_with(Arc::new(Gated(Arc::clone(&gate))));
+        open(&mut client, false, "fn f() {}\n").await;
+
+        send_message(&mut client, code_action(2, 0, 8)).await;
+        let resp = receive(&mut client).await;
+        let execute = json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "workspace/executeCommand",
+            "params": resp["result"][0]["command"],
+        });
+        send_message(&mut client, execute.clone()).await;
+        send_message(&mut client, execute).await;
+
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 3);
+        assert_eq!(resp["error"]["code"], INVALID_REQUEST);
+
+        // the first one still gets its answer
+        gate.notify_one();
+        let edit = receive(&mut client).await;
+        assert_eq!(edit["method"], "workspace/applyEdit");
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 3);
+        assert_eq!(resp["result"], Value::Null);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_drops_generations_and_sets_exit_code() {
+        let gate = Arc::new(Notify::new());
+        let (mut client, server) =
+            start_with(Arc::new(Gated(Arc::clone(&gate))));
+        open(&mut client, false, "fn f() {}\n").await;
+
+        send_message(&mut client, code_action(2, 0, 8)).await;
+        let resp = receive(&mut client).await;
+        send_message(&mut client, json!({
+            "jsonrpc": "2.0",
+            "id": 3,
+            "method": "workspace/executeCommand",
+            "params": resp["result"][0]["command"],
+        }))
+        .await;
+        send_message(&mut client, json!({
+            "jsonrpc": "2.0", "id": 4, "method": "shutdown",
+        }))
+        .await;
+
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 3);
+        assert_eq!(resp["error"]["code"], REQUEST_CANCELLED);
+        assert_eq!(receive(&mut client).await["id"], 4);
+
+        // the generation is gone, no edit can follow
+        gate.notify_one();
+        send_message(&mut client, json!({"jsonrpc": "2.0", "method": "exit"}))
+            .await;
+        assert!(read_message(&mut client.0).await.unwrap().is_none());
+        assert_eq!(server.await.unwrap().unwrap(), 0);
+
+        // exiting without shutdown is no error, only the code tells
+        let (mut client, server) = start_with(Arc::new(Fixed));
+        send_message(&mut client, json!({"jsonrpc": "2.0", "method": "exit"}))
+            .await;
+        assert_eq!(server.await.unwrap().unwrap(), 1);
+    }
+}
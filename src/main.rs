@@ -1,47 +1,80 @@
 mod ai;
+mod config;
 mod languages;
-use ai::backend::{ LLMBackend, TokenUsage };
-use ai::gemini::GeminiBackend;
-use ai::groq_gpt_oss::GGPTOSSBackend;
+mod lsp;
+mod pending;
+mod request;
+mod rpc;
+use ai::registry::BackendRegistry;
+use arc_swap::ArcSwap;
+use config::{Config, DEFAULT_MAX_CONCURRENT_REQUESTS};
+use languages::registry::LanguageRegistry;
+use pending::{Finished, PendingRequests};
+use request::{
+    Action, GenerateRequest, Job, Protocol, RequestId, encode, send,
+};
 use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
 use std::path::Path;
 use std::sync::Arc;
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, split};
 use tokio::net::{UnixListener, UnixStream};
+use tokio::signal::unix::{SignalKind, signal};
+use tokio::sync::mpsc;
 
+/// A control line, told apart from a generation request by its `command`
 #[derive(Deserialize)]
-struct GenerateRequest {
-    source_code: String,
-    cursor_byte: usize,
-    backend: String,
-    file_type: String, 
+#[serde(deny_unknown_fields)]
+struct CommandRequest {
+    command: String,
+    /// Request a `cancel` applies to
     #[serde(default)]
-    context_snippets: Option<Vec<String>>,
+    id: Option<RequestId>,
 }
 
 #[derive(Serialize)]
-struct GenerateResponse {
-    start_byte: usize,
-    end_byte: usize,
-    body: String,
-    usage: Option<TokenUsage>,
+struct CommandResponse {
+    ok: bool,
     error: Option<String>,
 }
 
+/// Backends and languages, replaced together when the config is reloaded
+struct Registries {
+    backends: BackendRegistry,
+    languages: LanguageRegistry,
+    /// Requests generated at once for a connection
+    max_concurrent_requests: usize,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    // initialize backends
-    let gemini = Arc::new(GeminiBackend {
-        api_key: std::env::var("GEMINI_API_KEY")?,
-        model: "Gemini-2.5-flash".to_string(),
-    });
+    // a broken config is fatal, so mistakes do not go unnoticed
+    let config = load_config()?;
+    let registries =
+        Arc::new(ArcSwap::from_pointee(build_registries(&config)?));
 
-    let groq = Arc::new(GGPTOSSBackend {
-        api_key: std::env::var("GROQ_API_KEY")?,
-        model: "openai/gpt-oss-20b".to_string(),
+    // SIGHUP re-reads the config, leaving open connections alone
+    let mut hangup = signal(SignalKind::hangup())?;
+    let on_hangup = Arc::clone(&registries);
+    tokio::spawn(async move {
+        while hangup.recv().await.is_some() {
+            if let Err(e) = reload(&on_hangup).await {
+                eprintln!("reload failed, keeping previous config: {e:#}");
+            }
+        }
     });
 
-    let path = std::env::var("SOCKET_PATH").unwrap_or_else(|_| "/tmp/chace.sock".to_string());
+    // `chace lsp` is started by editors and talks to them over stdio
+    if std::env::args().nth(1).as_deref() == Some("lsp") {
+        let (stdin, stdout) = (tokio::io::stdin(), tokio::io::stdout());
+        let code = lsp::serve(stdin, stdout, registries).await?;
+        std::process::exit(code);
+    }
+
+    let path = std::env::var("SOCKET_PATH")
+        .ok()
+        .or(config.socket_path)
+        .unwrap_or_else(|| "/tmp/chace.sock".to_string());
     if Path::new(&path).exists() {
         std::fs::remove_file(&path)?;
     }
@@ -51,129 +84,529 @@ async fn main() -> anyhow::Result<()> {
 
     loop {
         let (socket, _) = listener.accept().await?;
-        let gemini = Arc::clone(&gemini);
-        let groq = Arc::clone(&groq);
+        let registries = Arc::clone(&registries);
 
         tokio::spawn(async move {
-            if let Err(e) = handle_connection(socket, gemini, groq).await {
+            if let Err(e) = handle_connection(socket, registries).await {
                 eprintln!("connection error: {e}");
             }
         });
     }
 }
 
+fn load_config() -> anyhow::Result<Config> {
+    match Config::default_path() {
+        Some(path) => Config::load(&path),
+        None => Ok(Config::default()),
+    }
+}
+
+/// Sets up the backends and languages described by the config
+fn build_registries(config: &Config) -> anyhow::Result<Registries> {
+    let mut backends = BackendRegistry::from_env();
+    // user-defined backends may shadow the built-in ones
+    backends.register_configured(&config.backends);
+    backends.set_default(config.default_backend.clone());
+    if backends.names().is_empty() {
+        eprintln!("no backend configured, requests will fail");
+    } else {
+        eprintln!("Backends: {}", backends.names().join(", "));
+    }
+
+    let mut languages = LanguageRegistry::with_builtin();
+    languages.apply_config(&config.languages)?;
+
+    Ok(Registries {
+        backends,
+        languages,
+        max_concurrent_requests: config
+            .max_concurrent_requests
+            .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS),
+    })
+}
+
+/// Re-reads the config and swaps in the new registries. Requests already
+/// running keep the registries they started with; on error nothing changes.
+async fn reload(registries: &ArcSwap<Registries>) -> anyhow::Result<()> {
+    // key commands and grammar libraries block, so they get a thread
+    let rebuilt = tokio::task::spawn_blocking(|| {
+        let config = load_config()?;
+        build_registries(&config)
+    })
+    .await??;
+    registries.store(Arc::new(rebuilt));
+    eprintln!("Configuration reloaded");
+    Ok(())
+}
+
 async fn handle_connection(
     socket: UnixStream,
-    gemini: Arc<GeminiBackend>,
-    groq: Arc<GGPTOSSBackend>,
+    registries: Arc<ArcSwap<Registries>>,
 ) -> anyhow::Result<()> {
     let (reader, mut writer) = split(socket);
-    let mut reader = BufReader::new(reader);
-    let mut line = String::new();
-
-    while reader.read_line(&mut line).await? != 0 {
-        let req: GenerateRequest = match serde_json::from_str(&line) {
-            Ok(r) => r,
-            Err(e) => {
-                writer
-                    .write_all(format!("{{\"error\":\"{e}\"}}\n").as_bytes())
-                    .await?;
-                line.clear();
-                continue;
-            }
-        };
+    let mut lines = BufReader::new(reader).lines();
+
+    // responses and stream chunks all go through one writer
+    let (out, mut outgoing) = mpsc::unbounded_channel::<String>();
+    let writer_task = tokio::spawn(async move {
+        while let Some(message) = outgoing.recv().await {
+            writer.write_all(message.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+        }
+        Ok::<_, std::io::Error>(())
+    });
+
+    // requests run concurrently up to the configured limit, the others
+    // wait their turn; responses go out as they complete. Those without an
+    // id can only be told apart by their order, so they run one at a time.
+    let mut waiting: VecDeque<Job> = VecDeque::new();
+    let mut running: PendingRequests<Protocol> = PendingRequests::new();
+    let mut reading = true;
 
-        let resp = handle_request(req, &gemini, &groq).await;
-        let json = serde_json::to_string(&resp)?;
-        writer.write_all(json.as_bytes()).await?;
-        writer.write_all(b"\n").await?;
+    loop {
+        while running.len() < registries.load().max_concurrent_requests
+            && let Some(pos) = waiting
+                .iter()
+                .position(|job| job.id().is_some() || !running.has_unnamed())
+            && let Some(job) = waiting.remove(pos)
+        {
+            // held until the response is sent, even across a reload
+            let current = registries.load_full();
+            let id = job.id().cloned();
+            let protocol = job.protocol();
+            let out = out.clone();
+            running.spawn(id, protocol, async move {
+                job.run(&current, &out).await
+            });
+        }
+        if !reading && running.is_empty() {
+            break;
+        }
 
-        line.clear();
+        tokio::select! {
+            line = lines.next_line(), if reading => {
+                let Some(line) = line? else {
+                    // answer what was already sent before closing
+                    reading = false;
+                    continue;
+                };
+
+                match parse_line(&line, &registries) {
+                    Action::None => {}
+                    Action::Reply(message) => {
+                        let _ = out.send(message);
+                    }
+                    // responses are told apart by id, which must be unique
+                    Action::Queue(job)
+                        if job.id().is_some_and(|id| {
+                            is_pending(id, &waiting, &running)
+                        }) =>
+                    {
+                        let _ = out.send(job.protocol().error(
+                            job.id().cloned(),
+                            rpc::INVALID_REQUEST,
+                            "Duplicate request id",
+                        ));
+                    }
+                    Action::Queue(job) => waiting.push_back(*job),
+                    Action::Cancel(id, protocol) => {
+                        let found =
+                            cancel(&id, &mut waiting, &mut running, &out);
+                        // JSON-RPC cancels are notifications, only the
+                        // request itself is answered
+                        if let Protocol::Line = protocol {
+                            send(&out, &CommandResponse {
+                                ok: found,
+                                error: (!found).then(|| {
+                                    "No pending request with that id".into()
+                                }),
+                            });
+                        }
+                    }
+                    Action::Reload(reply) => {
+                        // requests go on being read and answered meanwhile
+                        let registries = Arc::clone(&registries);
+                        let out = out.clone();
+                        tokio::spawn(async move {
+                            let _ = out.send(reply(reload(&registries).await));
+                        });
+                    }
+                    Action::Shutdown(message) => {
+                        let _ = out.send(message);
+                        reading = false;
+                    }
+                }
+            }
+            finished = running.next(), if !running.is_empty() => {
+                match finished {
+                    Some(Finished::Answered(message)) => {
+                        let _ = out.send(message);
+                    }
+                    Some(Finished::Failed(id, protocol)) => {
+                        let _ = out.send(protocol.error(
+                            id,
+                            rpc::SERVER_ERROR,
+                            "Request failed",
+                        ));
+                    }
+                    None => {}
+                }
+            }
+        }
     }
 
+    drop(out);
+    writer_task.await??;
     Ok(())
 }
 
-async fn handle_request(
-    req: GenerateRequest,
-    gemini: &Arc<GeminiBackend>,
-    groq: &Arc<GGPTOSSBackend>,
-) -> GenerateResponse {
-    use languages::rust_backend::RustBackend;
-    use languages::ts_backend::TsBackend;
-    use languages::tsx_backend::TsxBackend;
-    use languages::js_backend::JsBackend;
-    use languages::jsx_backend::JsxBackend;
-    use languages::language_standard::LanguageStandard;
-
-    let backend_opt: Option<Box<dyn LanguageStandard + Send>> = match req.file_type.as_str() {
-        "rust" => Some(Box::new(RustBackend)),
-        "ts"| "typescript" => Some(Box::new(TsBackend)),
-        "tsx"| "typescriptreact" => Some(Box::new(TsxBackend)),
-        "js"| "javascript" => Some(Box::new(JsBackend)),
-        "jsx"| "javascriptreact" => Some(Box::new(JsxBackend)),
-        _ => None,
+/// Reads a line of either protocol, JSON-RPC messages being told apart by
+/// their `jsonrpc` member
+fn parse_line(line: &str, registries: &ArcSwap<Registries>) -> Action {
+    let message = match serde_json::from_str::<serde_json::Value>(line) {
+        Ok(message) => message,
+        // answered in the protocol the line seems to be written in
+        Err(e) if line.contains("\"jsonrpc\"") => {
+            let message = format!("Parse error: {e}");
+            return Action::Reply(rpc::error(None, rpc::PARSE_ERROR, &message));
+        }
+        Err(e) => {
+            let error = serde_json::json!({ "error": e.to_string() });
+            return Action::Reply(encode(&error));
+        }
     };
 
-    let Some(backend) = backend_opt else {
-        return GenerateResponse {
-            start_byte: 0,
-            end_byte: 0,
-            body: String::new(),
-            usage: None,
-            error: Some("Unsupported language".into()),
-        };
-    };
+    if rpc::is_rpc(&message) {
+        return rpc::parse(message, registries);
+    }
 
-    let Some(func) = backend.find_empty_function_at_cursor(
-        &req.source_code,
-        req.cursor_byte,
-    ) else {
-        return GenerateResponse {
-            start_byte: 0,
-            end_byte: 0,
-            body: String::new(),
-            usage: None,
-            error: Some("No empty function".into()),
+    if let Ok(cmd) = CommandRequest::deserialize(&message) {
+        return match (cmd.command.as_str(), cmd.id) {
+            ("cancel", Some(id)) => Action::Cancel(id, Protocol::Line),
+            ("cancel", None) => Action::Reply(encode(&CommandResponse {
+                ok: false,
+                error: Some("cancel needs the id of a request".into()),
+            })),
+            ("reload", _) => Action::Reload(Box::new(|result| {
+                encode(&match result {
+                    Ok(()) => CommandResponse { ok: true, error: None },
+                    Err(e) => CommandResponse {
+                        ok: false,
+                        error: Some(format!("{e:#}")),
+                    },
+                })
+            })),
+            (other, _) => Action::Reply(encode(&CommandResponse {
+                ok: false,
+                error: Some(format!("Unknown command: {other}")),
+            })),
         };
-    };
+    }
 
-    let backend: &dyn LLMBackend = match req.backend.as_str() {
-        "Gemini" => gemini.as_ref(),
-        "groq" => groq.as_ref(),
-        _ => {
-            return GenerateResponse {
-                start_byte: 0,
-                end_byte: 0,
-                body: String::new(),
-                usage: None,
-                error: Some("Unknown backend".into()),
-            };
+    match GenerateRequest::deserialize(message) {
+        Ok(req) => Action::Queue(Box::new(Job::Generate(req))),
+        Err(e) => {
+            let error = serde_json::json!({ "error": e.to_string() });
+            Action::Reply(encode(&error))
         }
-    };
+    }
+}
+
+fn is_pending(
+    id: &RequestId,
+    waiting: &VecDeque<Job>,
+    running: &PendingRequests<Protocol>,
+) -> bool {
+    running.contains(id) || waiting.iter().any(|job| job.id() == Some(id))
+}
 
-    match backend
-        .generate_function(
-            &func.signature,
-            func.doc_comment.as_deref(),
-            req.context_snippets.as_deref(),
-            req.file_type.as_ref(),
-        )
-        .await
+/// Drops the request with `id`, running or waiting, answering it with a
+/// `cancelled` error. Returns whether there was such a request.
+fn cancel(
+    id: &RequestId,
+    waiting: &mut VecDeque<Job>,
+    running: &mut PendingRequests<Protocol>,
+    out: &mpsc::UnboundedSender<String>,
+) -> bool {
+    let protocol = if let Some(protocol) = running.cancel(id) {
+        protocol
+    } else if let Some(job) = waiting
+        .iter()
+        .position(|job| job.id() == Some(id))
+        .and_then(|pos| waiting.remove(pos))
     {
-        Ok(res) => GenerateResponse {
-            start_byte: func.start_byte,
-            end_byte: func.end_byte,
-            body: res.body,
-            usage: res.usage,
-            error: None,
-        },
-        Err(e) => GenerateResponse {
-            start_byte: func.start_byte,
-            end_byte: func.end_byte,
-            body: String::new(),
-            usage: None,
-            error: Some(e.to_string()),
-        },
+        job.protocol()
+    } else {
+        return false;
+    };
+
+    let message =
+        protocol.error(Some(id.clone()), rpc::REQUEST_CANCELLED, "cancelled");
+    let _ = out.send(message);
+    true
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ai::backend::{GenerationOptions, LLMBackend, LLMResponse};
+    use async_trait::async_trait;
+    use tokio::io::Lines;
+    use tokio::sync::Notify;
+
+    /// A backend whose generations never finish
+    struct Hanging;
+
+    #[async_trait]
+    impl LLMBackend for Hanging {
+        async fn generate_function(
+            &self,
+            _signature: &str,
+            _doc_comment: Option<&str>,
+            _instructions: Option<&str>,
+            _context_snippets: Option<&[String]>,
+            _language: &str,
+            _options: &GenerationOptions,
+        ) -> anyhow::Result<LLMResponse> {
+            std::future::pending().await
+        }
+    }
+
+    /// A backend answering once the gate is opened
+    struct Gated(Arc<Notify>);
+
+    #[async_trait]
+    impl LLMBackend for Gated {
+        async fn generate_function(
+            &self,
+            _signature: &str,
+            _doc_comment: Option<&str>,
+            _instructions: Option<&str>,
+            _context_snippets: Option<&[String]>,
+            _language: &str,
+            _options: &GenerationOptions,
+        ) -> anyhow::Result<LLMResponse> {
+            self.0.notified().await;
+            Ok(LLMResponse { body: "gated".into(), usage: None })
+        }
+    }
+
+    type Client = (
+        Lines<BufReader<tokio::io::ReadHalf<UnixStream>>>,
+        tokio::io::WriteHalf<UnixStream>,
+    );
+
+    fn connect() -> Client {
+        connect_with(Arc::new(Notify::new()))
+    }
+
+    /// Connects with backend `gated` answering once `gate` is opened
+    fn connect_with(gate: Arc<Notify>) -> Client {
+        let mut backends = BackendRegistry::default();
+        backends.register("hang", Arc::new(Hanging));
+        backends.register("gated", Arc::new(Gated(gate)));
+        let registries = Arc::new(ArcSwap::from_pointee(Registries {
+            backends,
+            languages: LanguageRegistry::with_builtin(),
+            max_concurrent_requests: 2,
+        }));
+
+        let (client, server) = UnixStream::pair().unwrap();
+        tokio::spawn(handle_connection(server, registries));
+        let (reader, writer) = split(client);
+        (BufReader::new(reader).lines(), writer)
+    }
+
+    async fn send_line(client: &mut Client, message: serde_json::Value) {
+        let line = format!("{message}\n");
+        client.1.write_all(line.as_bytes()).await.unwrap();
+    }
+
+    async fn receive(client: &mut Client) -> serde_json::Value {
+        let line = client.0.next_line().await.unwrap().unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn generate(id: u32) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "source_code": "fn f() {}",
+            "cursor_byte": 8,
+            "file_type": "rust",
+            "backend": "hang"
+        })
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_and_waiting_requests() {
+        let mut client = connect();
+        send_line(&mut client, generate(1)).await;
+        send_line(&mut client, generate(2)).await;
+        send_line(&mut client, generate(3)).await;
+
+        // past the limit, 3 is dropped without ever starting
+        let cancel = serde_json::json!({"command": "cancel", "id": 3});
+        send_line(&mut client, cancel).await;
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 3);
+        assert_eq!(resp["error"], "cancelled");
+        assert_eq!(receive(&mut client).await["ok"], true);
+
+        let cancel = serde_json::json!({"command": "cancel", "id": 1});
+        send_line(&mut client, cancel).await;
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 1);
+        assert_eq!(resp["error"], "cancelled");
+        assert_eq!(receive(&mut client).await["ok"], true);
+
+        let cancel = serde_json::json!({"command": "cancel", "id": 1});
+        send_line(&mut client, cancel).await;
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["ok"], false);
+        assert_eq!(resp["error"], "No pending request with that id");
+    }
+
+    #[tokio::test]
+    async fn test_requests_after_a_cancel_are_served() {
+        let mut client = connect();
+        send_line(&mut client, generate(1)).await;
+        let cancel = serde_json::json!({"command": "cancel", "id": 1});
+        send_line(&mut client, cancel).await;
+        assert_eq!(receive(&mut client).await["error"], "cancelled");
+        assert_eq!(receive(&mut client).await["ok"], true);
+
+        let mut req = generate(2);
+        req["file_type"] = "cobol".into();
+        send_line(&mut client, req).await;
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 2);
+        assert_eq!(resp["error"], "Unsupported language");
+    }
+
+    #[tokio::test]
+    async fn test_requests_run_concurrently_up_to_the_limit() {
+        let mut client = connect();
+        send_line(&mut client, generate(1)).await;
+        send_line(&mut client, generate(2)).await;
+        let mut unsupported = generate(3);
+        unsupported["file_type"] = "cobol".into();
+        send_line(&mut client, unsupported.clone()).await;
+
+        // with both slots taken, 3 only starts once 1 is cancelled
+        let cancel = serde_json::json!({"command": "cancel", "id": 1});
+        send_line(&mut client, cancel).await;
+        assert_eq!(receive(&mut client).await["id"], 1);
+        assert_eq!(receive(&mut client).await["ok"], true);
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 3);
+        assert_eq!(resp["error"], "Unsupported language");
+
+        // 2 is still running, and does not hold up later requests
+        unsupported["id"] = 4.into();
+        send_line(&mut client, unsupported).await;
+        assert_eq!(receive(&mut client).await["id"], 4);
+
+        send_line(&mut client, generate(2)).await;
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 2);
+        assert_eq!(resp["error"], "Duplicate request id");
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_id_are_answered_in_order() {
+        let gate = Arc::new(Notify::new());
+        let mut client = connect_with(Arc::clone(&gate));
+        let mut gated = generate(1);
+        gated.as_object_mut().unwrap().remove("id");
+        gated["backend"] = "gated".into();
+        let mut unsupported = gated.clone();
+        unsupported["file_type"] = "cobol".into();
+        send_line(&mut client, gated).await;
+        send_line(&mut client, unsupported.clone()).await;
+
+        // requests with an id still run alongside
+        unsupported["id"] = 3.into();
+        send_line(&mut client, unsupported).await;
+        assert_eq!(receive(&mut client).await["id"], 3);
+
+        gate.notify_one();
+        assert_eq!(receive(&mut client).await["body"], "gated");
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["error"], "Unsupported language");
+    }
+
+    #[tokio::test]
+    async fn test_line_errors_are_valid_json() {
+        let mut client = connect();
+        let mut req = generate(1);
+        req["cursor_byte"] = "\"8\"".into();
+        send_line(&mut client, req).await;
+        let resp = receive(&mut client).await;
+        assert_eq!(
+            resp["error"],
+            "invalid type: string \"\\\"8\\\"\", expected usize"
+        );
+
+        client.1.write_all(b"{\"jsonrpc\": \"2.0\",\n").await.unwrap();
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], serde_json::Value::Null);
+        assert_eq!(resp["error"]["code"], rpc::PARSE_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_json_rpc_cancel_and_shutdown() {
+        let mut client = connect();
+        let complete = |id: u32| {
+            serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "method": "complete/function",
+                "params": generate(id),
+            })
+        };
+        send_line(&mut client, complete(1)).await;
+        send_line(&mut client, complete(1)).await;
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 1);
+        assert_eq!(resp["error"]["message"], "Duplicate request id");
+
+        // JSON-RPC and line requests share the connection
+        let mut unsupported = generate(2);
+        unsupported["file_type"] = "cobol".into();
+        send_line(&mut client, unsupported).await;
+        assert_eq!(receive(&mut client).await["error"], "Unsupported language");
+
+        let cancel = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": "$/cancelRequest",
+            "params": {"id": 1},
+        });
+        send_line(&mut client, cancel).await;
+        let resp = receive(&mut client).await;
+        assert_eq!(resp["id"], 1);
+        assert_eq!(resp["error"]["code"], rpc::REQUEST_CANCELLED);
+
+        let mut all = complete(3);
+        all["method"] = "complete/all".into();
+        all["params"]["source_code"] = "fn f() { 1 }".into();
+        send_line(&mut client, all).await;
+        let shutdown = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 4,
+            "method": "shutdown",
+        });
+        send_line(&mut client, shutdown).await;
+
+        // the pending request is still answered before the connection closes
+        let mut responses =
+            [receive(&mut client).await, receive(&mut client).await];
+        responses.sort_by_key(|resp| resp["id"].as_u64());
+        let completions = &responses[0]["result"]["completions"];
+        assert_eq!(*completions, serde_json::json!([]));
+        assert_eq!(responses[1]["id"], 4);
+        assert_eq!(responses[1]["result"], serde_json::Value::Null);
+        assert!(client.0.next_line().await.unwrap().is_none());
     }
 }
@@ -0,0 +1,134 @@
+use crate::request::RequestId;
+use std::collections::HashMap;
+use tokio::task::{AbortHandle, Id, JoinSet};
+
+/// Generations running for a connection, each resolving to the message
+/// answering it. Those with an id can be cancelled, and a request whose id
+/// is pending must be refused, since answers are told apart by id.
+pub struct PendingRequests<T> {
+    running: JoinSet<String>,
+    /// Request of each running task, and what the caller keeps about it
+    tasks: HashMap<Id, (Option<RequestId>, T)>,
+    /// Running requests with an id
+    cancellable: HashMap<RequestId, AbortHandle>,
+}
+
+/// A request that is done, as told by [`PendingRequests::next`]
+pub enum Finished<T> {
+    /// The message answering it
+    Answered(String),
+    /// Its task panicked, so the caller must answer it with an error
+    Failed(Option<RequestId>, T),
+}
+
+impl<T> PendingRequests<T> {
+    pub fn new() -> Self {
+        PendingRequests {
+            running: JoinSet::new(),
+            tasks: HashMap::new(),
+            cancellable: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.running.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.running.is_empty()
+    }
+
+    pub fn contains(&self, id: &RequestId) -> bool {
+        self.cancellable.contains_key(id)
+    }
+
+    /// Whether a request without an id is running
+    pub fn has_unnamed(&self) -> bool {
+        self.tasks.values().any(|(id, _)| id.is_none())
+    }
+
+    pub fn spawn(
+        &mut self,
+        id: Option<RequestId>,
+        tag: T,
+        reply: impl Future<Output = String> + Send + 'static,
+    ) {
+        let handle = self.running.spawn(reply);
+        if let Some(id) = &id {
+            self.cancellable.insert(id.clone(), handle.clone());
+        }
+        self.tasks.insert(handle.id(), (id, tag));
+    }
+
+    /// Waits for a request to finish. Returns `None` when a cancel already
+    /// answered it. Cancel safe, for use in `select!`.
+    pub async fn next(&mut self) -> Option<Finished<T>> {
+        let (task, result) = match self.running.join_next_with_id().await? {
+            Ok((task, message)) => (task, Ok(message)),
+            Err(e) => (e.id(), Err(e)),
+        };
+        // a cancel may come after the task is done
+        let (id, tag) = self.tasks.remove(&task)?;
+        if let Some(id) = &id {
+            self.cancellable.remove(id);
+        }
+
+        match result {
+            Ok(message) => Some(Finished::Answered(message)),
+            Err(e) => {
+                eprintln!("request failed: {e}");
+                Some(Finished::Failed(id, tag))
+            }
+        }
+    }
+
+    /// Aborts the running request with `id`, returning its tag for the
+    /// caller to answer it, or `None` if there is no such request
+    pub fn cancel(&mut self, id: &RequestId) -> Option<T> {
+        let handle = self.cancellable.remove(id)?;
+        // dropping the task aborts the call to the backend
+        handle.abort();
+        self.tasks.remove(&handle.id()).map(|(_, tag)| tag)
+    }
+
+    /// Aborts every request, returning those with an id for the caller to
+    /// answer
+    pub fn cancel_all(&mut self) -> Vec<(RequestId, T)> {
+        self.running.abort_all();
+        self.cancellable.clear();
+        self.tasks
+            .drain()
+            .filter_map(|(_, (id, tag))| Some((id?, tag)))
+            .collect()
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_panicked_request_is_reported_and_its_id_freed() {
+        let mut pending = PendingRequests::new();
+        let id = RequestId::Number(1);
+        pending.spawn(Some(id.clone()), "tag", async { panic!("boom") });
+
+        match pending.next().await {
+            Some(Finished::Failed(failed, tag)) => {
+                assert_eq!(failed, Some(id.clone()));
+                assert_eq!(tag, "tag");
+            }
+            _ => panic!("the panic should be reported"),
+        }
+        assert!(!pending.contains(&id));
+
+        pending.spawn(Some(id.clone()), "tag", async { "done".to_string() });
+        assert!(pending.contains(&id));
+        match pending.next().await {
+            Some(Finished::Answered(message)) => assert_eq!(message, "done"),
+            _ => panic!("the request should be answered"),
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use crate::ai::backend::{GenerationOptions, LLMBackend, TokenUsage};
+use crate::ai::registry::BackendRegistry;
+use crate::languages::helpers::indent_body;
+use crate::languages::language_standard::{FunctionInfo, LanguageStandard};
+use crate::languages::registry::LanguageRegistry;
+use crate::{Registries, rpc};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Id a client gives a request, echoed in everything sent back for it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+#[serde(untagged)]
+pub enum RequestId {
+    Number(i64),
+    String(String),
+}
+
+#[derive(Deserialize)]
+pub struct GenerateRequest {
+    #[serde(default)]
+    pub id: Option<RequestId>,
+    pub source_code: String,
+    pub cursor_byte: usize,
+    #[serde(default)]
+    pub backend: Option<String>,
+    pub file_type: String, 
+    #[serde(default)]
+    pub context_snippets: Option<Vec<String>>,
+    /// Sends the output as chunks before the final response
+    #[serde(default)]
+    pub stream: bool,
+    /// `model`, `temperature`, `max_tokens` and `stop` overrides
+    #[serde(flatten)]
+    pub options: GenerationOptions,
+}
+
+#[derive(Serialize)]
+pub struct GenerateResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<RequestId>,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub body: String,
+    pub usage: Option<TokenUsage>,
+    pub error: Option<String>,
+}
+
+/// Raw output of a streamed request, sent as it is generated. The body to
+/// insert is the one of the final response.
+#[derive(Serialize)]
+pub struct StreamChunk<'a> {
+    id: Option<RequestId>,
+    start_byte: usize,
+    end_byte: usize,
+    delta: &'a str,
+}
+
+/// Receives the chunks of a streamed request
+pub type ChunkSink = Arc<dyn Fn(&StreamChunk<'_>) + Send + Sync>;
+
+/// Protocol of a line, which the answers to it are written in
+#[derive(Clone, Copy)]
+pub enum Protocol {
+    Line,
+    JsonRpc,
+}
+
+impl Protocol {
+    /// Error answering request `id`, `code` being used by JSON-RPC only
+    pub fn error(
+        self,
+        id: Option<RequestId>,
+        code: i64,
+        message: &str,
+    ) -> String {
+        match self {
+            Protocol::Line => encode(&GenerateResponse {
+                id,
+                ..GenerateResponse::error(message)
+            }),
+            Protocol::JsonRpc => rpc::error(id.as_ref(), code, message),
+        }
+    }
+}
+
+/// Generation waiting for, or holding, one of the connection's slots
+pub enum Job {
+    /// Request of the line protocol
+    Generate(GenerateRequest),
+    /// JSON-RPC `complete/function`
+    CompleteFunction(GenerateRequest),
+    /// JSON-RPC `complete/all`
+    CompleteAll(GenerateRequest),
+}
+
+impl Job {
+    pub fn id(&self) -> Option<&RequestId> {
+        match self {
+            Job::Generate(req)
+            | Job::CompleteFunction(req)
+            | Job::CompleteAll(req) => req.id.as_ref(),
+        }
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        match self {
+            Job::Generate(_) => Protocol::Line,
+            Job::CompleteFunction(_) | Job::CompleteAll(_) => {
+                Protocol::JsonRpc
+            }
+        }
+    }
+
+    /// Runs the generation, returning the message answering it
+    pub async fn run(
+        self,
+        registries: &Registries,
+        out: &mpsc::UnboundedSender<String>,
+    ) -> String {
+        let Registries { backends, languages, .. } = registries;
+        match self {
+            Job::Generate(req) => {
+                let id = req.id.clone();
+                let out = out.clone();
+                let chunks: ChunkSink =
+                    Arc::new(move |chunk: &StreamChunk<'_>| {
+                        send(&out, chunk)
+                    });
+                let resp =
+                    handle_request(req, backends, languages, &chunks).await;
+                encode(&GenerateResponse { id, ..resp })
+            }
+            Job::CompleteFunction(req) => {
+                rpc::complete_function(req, backends, languages, out).await
+            }
+            Job::CompleteAll(req) => {
+                rpc::complete_all(req, backends, languages, out).await
+            }
+        }
+    }
+}
+
+/// What a line asks of its connection
+pub enum Action {
+    /// Nothing, e.g. for a JSON-RPC notification
+    None,
+    Reply(String),
+    Queue(Box<Job>),
+    Cancel(RequestId, Protocol),
+    /// Reloads the config off the event loop, then sends the reply made
+    /// from the outcome
+    Reload(Box<dyn FnOnce(anyhow::Result<()>) -> String + Send>),
+    /// Sends the message, then closes once every request is answered
+    Shutdown(String),
+}
+
+impl GenerateResponse {
+    pub fn error(message: impl Into<String>) -> Self {
+        GenerateResponse {
+            id: None,
+            start_byte: 0,
+            end_byte: 0,
+            body: String::new(),
+            usage: None,
+            error: Some(message.into()),
+        }
+    }
+}
+
+/// Serializes a message; the ones of both protocols always serialize
+pub fn encode(message: &impl Serialize) -> String {
+    serde_json::to_string(message).expect("messages serialize to JSON")
+}
+
+/// Queues a message for the connection's writer
+pub fn send(out: &mpsc::UnboundedSender<String>, message: &impl Serialize) {
+    // fails only once the connection is gone
+    let _ = out.send(encode(message));
+}
+
+/// Generates the body of the empty function at the cursor
+pub async fn handle_request(
+    req: GenerateRequest,
+    backends: &BackendRegistry,
+    languages: &LanguageRegistry,
+    chunks: &ChunkSink,
+) -> GenerateResponse {
+    let language = match find_language(&req.file_type, languages) {
+        Ok(language) => language,
+        Err(e) => return GenerateResponse::error(e),
+    };
+
+    let Some(func) = language.find_empty_function_at_cursor(
+        &req.source_code,
+        req.cursor_byte,
+    ) else {
+        return GenerateResponse::error("No empty function");
+    };
+
+    match select_backend(&req, backends, languages) {
+        Ok(backend) => generate(&req, &func, backend.as_ref(), chunks).await,
+        Err(e) => GenerateResponse::error(e),
+    }
+}
+
+/// Generates the bodies of every empty function of the source, one after
+/// the other. Their byte ranges are all relative to the source sent.
+pub async fn handle_all(
+    req: GenerateRequest,
+    backends: &BackendRegistry,
+    languages: &LanguageRegistry,
+    chunks: &ChunkSink,
+) -> Result<Vec<GenerateResponse>, String> {
+    let language = find_language(&req.file_type, languages)?;
+    let functions = language.find_empty_functions(&req.source_code);
+    if functions.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let backend = select_backend(&req, backends, languages)?;
+    let mut responses = Vec::with_capacity(functions.len());
+    for func in &functions {
+        responses.push(generate(&req, func, backend.as_ref(), chunks).await);
+    }
+    Ok(responses)
+}
+
+fn find_language(
+    file_type: &str,
+    languages: &LanguageRegistry,
+) -> Result<Arc<dyn LanguageStandard>, String> {
+    languages.get(file_type).ok_or_else(|| {
+        languages
+            .load_error(file_type)
+            .unwrap_or("Unsupported language")
+            .to_string()
+    })
+}
+
+/// The request wins over the language, which wins over the default
+fn select_backend(
+    req: &GenerateRequest,
+    backends: &BackendRegistry,
+    languages: &LanguageRegistry,
+) -> Result<Arc<dyn LLMBackend>, String> {
+    let Some(backend_name) = req
+        .backend
+        .as_deref()
+        .or(languages.backend_for(&req.file_type))
+        .or(backends.default_name())
+    else {
+        return Err("No backend selected".into());
+    };
+
+    backends.get(backend_name).ok_or_else(|| {
+        backends
+            .not_configured(backend_name)
+            .unwrap_or_else(|| "Unknown backend".into())
+    })
+}
+
+async fn generate(
+    req: &GenerateRequest,
+    func: &FunctionInfo,
+    backend: &dyn LLMBackend,
+    chunks: &ChunkSink,
+) -> GenerateResponse {
+    let mut options = req.options.clone();
+    options.braced_body = func.indent.is_none()
+        && req.source_code[func.end_byte..].starts_with('}');
+    if req.stream {
+        let chunks = Arc::clone(chunks);
+        let id = req.id.clone();
+        let (start_byte, end_byte) = (func.start_byte, func.end_byte);
+        options.on_delta = Some(Arc::new(move |delta: &str| {
+            chunks(&StreamChunk {
+                id: id.clone(),
+                start_byte,
+                end_byte,
+                delta,
+            });
+        }));
+    }
+
+    // FIM models see the code around the body and produce it verbatim
+    let result = if backend.supports_fim() {
+        backend
+            .fill_in_middle(
+                &fim_prefix(req, func),
+                &req.source_code[func.end_byte..],
+                &options,
+            )
+            .await
+    } else {
+        backend
+            .generate_function(
+                &func.signature,
+                func.doc_comment.as_deref(),
+                func.instructions.as_deref(),
+                req.context_snippets.as_deref(),
+                req.file_type.as_ref(),
+                &options,
+            )
+            .await
+            .map(|mut res| {
+                if let Some(indent) = func.indent.as_deref() {
+                    res.body = indent_body(&res.body, indent);
+                }
+                res
+            })
+    };
+
+    match result {
+        Ok(res) => GenerateResponse {
+            id: None,
+            start_byte: func.start_byte,
+            end_byte: func.end_byte,
+            body: res.body,
+            usage: res.usage,
+            error: None,
+        },
+        Err(e) => GenerateResponse {
+            id: None,
+            start_byte: func.start_byte,
+            end_byte: func.end_byte,
+            body: String::new(),
+            usage: None,
+            error: Some(e.to_string()),
+        },
+    }
+}
+
+/// Code before the body for a fill-in-the-middle prompt. The body is kept
+/// up to its last comment, so the instructions precede the hole, and the
+/// context snippets come first, as if earlier in the file.
+fn fim_prefix(req: &GenerateRequest, func: &FunctionInfo) -> String {
+    let mut prefix = String::new();
+    for snippet in req.context_snippets.iter().flatten() {
+        prefix.push_str(snippet);
+        prefix.push_str("\n\n");
+    }
+    let end = func.instructions_end.unwrap_or(func.start_byte);
+    prefix.push_str(&req.source_code[..end]);
+    prefix
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::backend::LLMResponse;
+    use async_trait::async_trait;
+
+    /// A FIM backend answering with the prompt it was given
+    struct EchoFim;
+
+    #[async_trait]
+    impl LLMBackend for EchoFim {
+        async fn generate_function(
+            &self,
+            _signature: &str,
+            _doc_comment: Option<&str>,
+            _instructions: Option<&str>,
+            _context_snippets: Option<&[String]>,
+            _language: &str,
+            _options: &GenerationOptions,
+        ) -> anyhow::Result<LLMResponse> {
+            unreachable!("FIM is preferred")
+        }
+
+        fn supports_fim(&self) -> bool {
+            true
+        }
+
+        async fn fill_in_middle(
+            &self,
+            prefix: &str,
+            suffix: &str,
+            _options: &GenerationOptions,
+        ) -> anyhow::Result<LLMResponse> {
+            Ok(LLMResponse {
+                body: format!("{prefix}<HOLE>{suffix}"),
+                usage: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fim_prompt_keeps_instructions_and_snippets() {
+        let req: GenerateRequest = serde_json::from_value(serde_json::json!({
+            "source_code": "fn f() {\n    // use binary search\n    todo!()\n}",
+            "cursor_byte": 9,
+            "file_type": "rust",
+            "context_snippets": ["struct Index;"],
+        }))
+        .unwrap();
+        let languages = LanguageRegistry::with_builtin();
+        let func = languages
+            .get("rust")
+            .unwrap()
+            .find_empty_function_at_cursor(&req.source_code, req.cursor_byte)
+            .unwrap();
+        let chunks: ChunkSink = Arc::new(|_: &StreamChunk| {});
+
+        let resp = generate(&req, &func, &EchoFim, &chunks).await;
+
+        assert_eq!(
+            resp.body,
+            "struct Index;\n\nfn f() {\n    // use binary search<HOLE>}"
+        );
+        // the whole body is still replaced, comment and stub included
+        assert_eq!(
+            &req.source_code[resp.start_byte..resp.end_byte],
+            "\n    // use binary search\n    todo!()\n"
+        );
+    }
+}
@@ -0,0 +1,362 @@
+use crate::ai::registry::BackendRegistry;
+use crate::languages::registry::LanguageRegistry;
+use crate::Registries;
+use crate::request::{
+    Action, ChunkSink, GenerateRequest, Job, Protocol, RequestId, StreamChunk,
+    handle_all, handle_request, send,
+};
+use arc_swap::ArcSwap;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Version of the JSON-RPC interface, raised on incompatible changes
+pub const PROTOCOL_VERSION: &str = "1.0";
+
+/// Methods served, as listed in the capabilities
+const METHODS: [&str; 6] = [
+    "initialize",
+    "complete/function",
+    "complete/all",
+    "reload",
+    "shutdown",
+    "$/cancelRequest",
+];
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// A generation or a reload failed, the message says why
+pub const SERVER_ERROR: i64 = -32000;
+/// The request was dropped by `$/cancelRequest`
+pub const REQUEST_CANCELLED: i64 = -32800;
+
+#[derive(Deserialize)]
+struct Message {
+    jsonrpc: String,
+    /// Missing for notifications, which are never answered
+    #[serde(default)]
+    id: Option<RequestId>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Serialize)]
+struct Response<'a> {
+    jsonrpc: &'static str,
+    /// `null` when the id of the request could not be read
+    id: Option<&'a RequestId>,
+    #[serde(flatten)]
+    outcome: Outcome,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Outcome {
+    Result(Value),
+    Error { code: i64, message: String },
+}
+
+#[derive(Serialize)]
+struct Notification<'a, T> {
+    jsonrpc: &'static str,
+    method: &'a str,
+    params: T,
+}
+
+#[derive(Deserialize)]
+struct CancelParams {
+    id: RequestId,
+}
+
+/// Whether a message is meant for this interface rather than the line
+/// protocol
+pub fn is_rpc(message: &Value) -> bool {
+    match message {
+        Value::Object(members) => members.contains_key("jsonrpc"),
+        // batches only exist in JSON-RPC
+        Value::Array(_) => true,
+        _ => false,
+    }
+}
+
+/// Reads a JSON-RPC message, answering it right away unless it queues a
+/// generation
+pub fn parse(message: Value, registries: &ArcSwap<Registries>) -> Action {
+    if message.is_array() {
+        let message = "Batches are not supported";
+        return Action::Reply(error(None, INVALID_REQUEST, message));
+    }
+
+    // invalid requests are still answered to their id, if it can be read
+    let id = message
+        .get("id")
+        .and_then(|id| RequestId::deserialize(id).ok());
+    let message = match Message::deserialize(message) {
+        Ok(message) if message.jsonrpc == "2.0" => message,
+        Ok(_) => {
+            let message = "Unsupported jsonrpc version, expected 2.0";
+            return Action::Reply(error(id.as_ref(), INVALID_REQUEST, message));
+        }
+        Err(e) => {
+            let message = format!("Invalid request: {e}");
+            return Action::Reply(error(id.as_ref(), INVALID_REQUEST, &message));
+        }
+    };
+
+    let Some(id) = message.id else {
+        return notification(&message.method, message.params);
+    };
+
+    match message.method.as_str() {
+        "initialize" => {
+            Action::Reply(result(Some(&id), capabilities(&registries.load())))
+        }
+        "complete/function" => {
+            match GenerateRequest::deserialize(message.params) {
+                Ok(req) => Action::Queue(Box::new(Job::CompleteFunction(
+                    GenerateRequest { id: Some(id), ..req },
+                ))),
+                Err(e) => invalid_params(&id, e),
+            }
+        }
+        "complete/all" => {
+            // every function is completed, wherever the cursor is
+            let mut params = message.params;
+            if let Some(params) = params.as_object_mut() {
+                params.entry("cursor_byte").or_insert(0.into());
+            }
+            match GenerateRequest::deserialize(params) {
+                Ok(req) => Action::Queue(Box::new(Job::CompleteAll(
+                    GenerateRequest { id: Some(id), ..req },
+                ))),
+                Err(e) => invalid_params(&id, e),
+            }
+        }
+        "reload" => Action::Reload(Box::new(move |outcome| match outcome {
+            Ok(()) => result(Some(&id), Value::Null),
+            Err(e) => error(Some(&id), SERVER_ERROR, &format!("{e:#}")),
+        })),
+        "shutdown" => Action::Shutdown(result(Some(&id), Value::Null)),
+        "$/cancelRequest" => {
+            let message = "$/cancelRequest is a notification, send no id";
+            Action::Reply(error(Some(&id), INVALID_REQUEST, message))
+        }
+        other => {
+            let message = format!("Method not found: {other}");
+            Action::Reply(error(Some(&id), METHOD_NOT_FOUND, &message))
+        }
+    }
+}
+
+/// Notifications are never answered, not even when they are invalid
+fn notification(method: &str, params: Value) -> Action {
+    match method {
+        "$/cancelRequest" => match CancelParams::deserialize(params) {
+            Ok(params) => Action::Cancel(params.id, Protocol::JsonRpc),
+            Err(_) => Action::None,
+        },
+        _ => Action::None,
+    }
+}
+
+fn invalid_params(id: &RequestId, e: serde_json::Error) -> Action {
+    let message = format!("Invalid params: {e}");
+    Action::Reply(error(Some(id), INVALID_PARAMS, &message))
+}
+
+/// Result of `initialize`
+fn capabilities(registries: &Registries) -> Value {
+    let Registries {
+        backends,
+        languages,
+        max_concurrent_requests,
+    } = registries;
+
+    json!({
+        "protocol_version": PROTOCOL_VERSION,
+        "server_info": {
+            "name": env!("CARGO_PKG_NAME"),
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "capabilities": {
+            "methods": METHODS,
+            "languages": languages.file_types(),
+            "backends": backends.names(),
+            "default_backend": backends.default_name(),
+            // other backends answer streamed requests without chunks
+            "streaming": backends.streaming_names(),
+            "max_concurrent_requests": max_concurrent_requests,
+        },
+    })
+}
+
+pub fn result(id: Option<&RequestId>, result: Value) -> String {
+    encode(id, Outcome::Result(result))
+}
+
+pub fn error(id: Option<&RequestId>, code: i64, message: &str) -> String {
+    let message = message.to_string();
+    encode(id, Outcome::Error { code, message })
+}
+
+fn encode(id: Option<&RequestId>, outcome: Outcome) -> String {
+    crate::request::encode(&Response {
+        jsonrpc: "2.0",
+        id,
+        outcome,
+    })
+}
+
+/// Sends the chunks of streamed requests as `complete/chunk` notifications
+fn chunk_sink(out: &mpsc::UnboundedSender<String>) -> ChunkSink {
+    let out = out.clone();
+    Arc::new(move |chunk: &StreamChunk<'_>| {
+        send(&out, &Notification {
+            jsonrpc: "2.0",
+            method: "complete/chunk",
+            params: chunk,
+        });
+    })
+}
+
+/// Answers `complete/function` with the body of the function at the cursor
+pub async fn complete_function(
+    req: GenerateRequest,
+    backends: &BackendRegistry,
+    languages: &LanguageRegistry,
+    out: &mpsc::UnboundedSender<String>,
+) -> String {
+    let id = req.id.clone();
+    let chunks = chunk_sink(out);
+    let resp = handle_request(req, backends, languages, &chunks).await;
+
+    match resp.error {
+        Some(message) => error(id.as_ref(), SERVER_ERROR, &message),
+        None => result(
+            id.as_ref(),
+            json!({
+                "start_byte": resp.start_byte,
+                "end_byte": resp.end_byte,
+                "body": resp.body,
+                "usage": resp.usage,
+            }),
+        ),
+    }
+}
+
+/// Answers `complete/all` with a completion per empty function, each
+/// carrying its own error if its generation failed
+pub async fn complete_all(
+    req: GenerateRequest,
+    backends: &BackendRegistry,
+    languages: &LanguageRegistry,
+    out: &mpsc::UnboundedSender<String>,
+) -> String {
+    let id = req.id.clone();
+    let chunks = chunk_sink(out);
+
+    match handle_all(req, backends, languages, &chunks).await {
+        Ok(completions) => {
+            result(id.as_ref(), json!({ "completions": completions }))
+        }
+        Err(message) => error(id.as_ref(), SERVER_ERROR, &message),
+    }
+}
+
+//-----------------------------Unit Tests--------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registries() -> ArcSwap<Registries> {
+        ArcSwap::from_pointee(Registries {
+            backends: BackendRegistry::default(),
+            languages: LanguageRegistry::with_builtin(),
+            max_concurrent_requests: 2,
+        })
+    }
+
+    fn reply(line: &str) -> Value {
+        let message = serde_json::from_str(line).unwrap();
+        match parse(message, &registries()) {
+            Action::Reply(reply) => serde_json::from_str(&reply).unwrap(),
+            _ => panic!("no reply to {line}"),
+        }
+    }
+
+    #[test]
+    fn test_initialize_lists_capabilities() {
+        let resp = reply(r#"{"jsonrpc":"2.0","id":1,"method":"initialize"}"#);
+        assert_eq!(resp["jsonrpc"], "2.0");
+        assert_eq!(resp["id"], 1);
+        assert_eq!(resp["result"]["protocol_version"], PROTOCOL_VERSION);
+
+        let capabilities = &resp["result"]["capabilities"];
+        let languages = capabilities["languages"].as_array().unwrap();
+        assert!(languages.contains(&"rust".into()));
+        assert!(languages.contains(&"typescript".into()));
+        assert_eq!(capabilities["backends"], json!([]));
+        assert_eq!(capabilities["default_backend"], Value::Null);
+        assert_eq!(capabilities["streaming"], json!([]));
+        assert_eq!(capabilities["methods"][1], "complete/function");
+    }
+
+    #[test]
+    fn test_invalid_messages_get_standard_errors() {
+        let resp = reply(r#"{"jsonrpc":"2.0","id":"a","method":"nope"}"#);
+        assert_eq!(resp["id"], "a");
+        assert_eq!(resp["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(resp["error"]["message"], "Method not found: nope");
+        assert!(resp.get("result").is_none());
+
+        let resp = reply(r#"{"jsonrpc":"1.0","id":2,"method":"initialize"}"#);
+        assert_eq!(resp["id"], 2);
+        assert_eq!(resp["error"]["code"], INVALID_REQUEST);
+
+        let resp = reply(r#"{"jsonrpc":"2.0","id":3}"#);
+        assert_eq!(resp["error"]["code"], INVALID_REQUEST);
+
+        let resp = reply(
+            r#"{"jsonrpc":"2.0","id":4,"method":"complete/function",
+                "params":{"file_type":"rust"}}"#,
+        );
+        assert_eq!(resp["error"]["code"], INVALID_PARAMS);
+
+        let resp = reply(r#"[{"jsonrpc":"2.0","id":5,"method":"shutdown"}]"#);
+        assert_eq!(resp["id"], Value::Null);
+        assert_eq!(resp["error"]["code"], INVALID_REQUEST);
+    }
+
+    #[test]
+    fn test_notifications_are_not_answered() {
+        let registries = registries();
+        let parse_line = |line: &str| {
+            parse(serde_json::from_str(line).unwrap(), &registries)
+        };
+
+        let action = parse_line(r#"{"jsonrpc":"2.0","method":"initialized"}"#);
+        assert!(matches!(action, Action::None));
+
+        let action = parse_line(
+            r#"{"jsonrpc":"2.0","method":"$/cancelRequest","params":{"id":7}}"#,
+        );
+        assert!(matches!(
+            action,
+            Action::Cancel(RequestId::Number(7), Protocol::JsonRpc)
+        ));
+
+        let action = parse_line(
+            r#"{"jsonrpc":"2.0","id":8,"method":"complete/all",
+                "params":{"source_code":"","file_type":"rust"}}"#,
+        );
+        assert!(matches!(
+            action,
+            Action::Queue(job) if matches!(*job, Job::CompleteAll(_))
+        ));
+    }
+}
@@ -89,7 +89,7 @@ async fn test_unsupported_language() {
         source_code: "fn test() {}".to_string(),
         cursor_byte: 10,
         backend: "Gemini".to_string(),
-        file_type: "python".to_string(),
+        file_type: "cobol".to_string(),
         context_snippets: None,
     };
 
@@ -351,3 +351,121 @@ async fn test_multiple_sequential_requests() {
         tokio::time::sleep(Duration::from_millis(50)).await;
     }
 }
+
+#[tokio::test]
+async fn test_reload_keeps_connection_open() {
+    let socket_path = "/tmp/chace_test.sock";
+
+    // Wait for socket
+    for _ in 0..10 {
+        if Path::new(socket_path).exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let stream = UnixStream::connect(socket_path).await.unwrap();
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+
+    writer.write_all(b"{\"command\":\"reload\"}\n").await.unwrap();
+    let mut response = String::new();
+    reader.read_line(&mut response).await.unwrap();
+    let resp: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(resp["ok"], true);
+
+    writer.write_all(b"{\"command\":\"restart\"}\n").await.unwrap();
+    response.clear();
+    reader.read_line(&mut response).await.unwrap();
+    let resp: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(resp["ok"], false);
+    assert_eq!(resp["error"], "Unknown command: restart");
+
+    // The same connection still serves requests
+    let req = GenerateRequest {
+        source_code: "fn test() {}".to_string(),
+        cursor_byte: 5,
+        backend: "Gemini".to_string(),
+        file_type: "cobol".to_string(),
+        context_snippets: None,
+    };
+    let json = serde_json::to_string(&req).unwrap();
+    writer.write_all(json.as_bytes()).await.unwrap();
+    writer.write_all(b"\n").await.unwrap();
+    response.clear();
+    reader.read_line(&mut response).await.unwrap();
+    let resp: GenerateResponse = serde_json::from_str(&response).unwrap();
+    assert_eq!(resp.error.as_deref(), Some("Unsupported language"));
+}
+
+#[tokio::test]
+async fn test_request_id_is_echoed() {
+    let socket_path = "/tmp/chace_test.sock";
+
+    // Wait for socket
+    for _ in 0..10 {
+        if Path::new(socket_path).exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let stream = UnixStream::connect(socket_path).await.unwrap();
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+
+    let request = serde_json::json!({
+        "id": "buf-3",
+        "source_code": "fn has_code() { 1 }",
+        "cursor_byte": 14,
+        "file_type": "rust",
+        "stream": true
+    });
+    writer
+        .write_all(format!("{request}\n").as_bytes())
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    reader.read_line(&mut response).await.unwrap();
+    let resp: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(resp["id"], "buf-3");
+    assert_eq!(resp["error"], "No empty function");
+}
+
+#[tokio::test]
+async fn test_json_rpc_initialize() {
+    let socket_path = "/tmp/chace_test.sock";
+
+    // Wait for socket
+    for _ in 0..10 {
+        if Path::new(socket_path).exists() {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+
+    let stream = UnixStream::connect(socket_path).await.unwrap();
+    let (reader, mut writer) = tokio::io::split(stream);
+    let mut reader = BufReader::new(reader);
+
+    writer
+        .write_all(b"{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\"}\n")
+        .await
+        .unwrap();
+
+    let mut response = String::new();
+    reader.read_line(&mut response).await.unwrap();
+    let resp: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(resp["id"], 1);
+    assert_eq!(resp["result"]["protocol_version"], "1.0");
+    let backends = &resp["result"]["capabilities"]["backends"];
+    assert!(backends.as_array().unwrap().contains(&"groq".into()));
+
+    // the line protocol is still served on the same connection
+    writer.write_all(b"{\"command\": \"nope\"}\n").await.unwrap();
+    response.clear();
+    reader.read_line(&mut response).await.unwrap();
+    let resp: serde_json::Value = serde_json::from_str(&response).unwrap();
+    assert_eq!(resp["error"], "Unknown command: nope");
+}